@@ -1,7 +1,14 @@
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::ops::FnOnce;
 
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+pub use serde as __serde;
+
 /// `TypedBuilder` is not a real type - deriving it will generate a `::builder()` method on your
 /// struct that will return a compile-time checked builder. Set the fields using setters with the
 /// same name as the struct's fields and call `.build()` when you are done to create your object.
@@ -52,6 +59,61 @@ use core::ops::FnOnce;
 /// // Foo::builder().x(1).y(2).y(3);
 /// ```
 ///
+/// # Enums
+///
+/// When deriving on an enum, every variant with named fields gets its own builder. The builder
+/// method is the variant's name in snake case followed by `_builder`, and the builder type is the
+/// enum's name followed by the variant's name and `Builder`. Unit and tuple variants are ignored.
+///
+/// `#[builder(...)]` attributes on the enum apply to all the variants, and each variant can have
+/// its own `#[builder(...)]` attribute on top of them - e.g. to rename its builder method or
+/// builder type, which can only be done per variant.
+///
+/// ```
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(PartialEq, TypedBuilder)]
+/// enum Event {
+///     Click {
+///         x: i32,
+///         y: i32,
+///         #[builder(default = 1)]
+///         button: u8,
+///     },
+///     #[builder(builder_method(name = key))]
+///     KeyPress {
+///         key: char,
+///     },
+///     Close,
+/// }
+///
+/// assert!(
+///     Event::click_builder().x(1).y(2).build()
+///     == Event::Click { x: 1, y: 2, button: 1 });
+///
+/// assert!(Event::key().key('a').build() == Event::KeyPress { key: 'a' });
+/// ```
+///
+/// # Tuple structs
+///
+/// Tuple structs get setters named after the position of each field - `_0`, `_1` and so on -
+/// unless the field is given a name with `#[builder(setter(name = ...))]`. That name is also the
+/// one defaults of other fields can use to refer to it.
+///
+/// ```
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(PartialEq, TypedBuilder)]
+/// struct Rgb(
+///     #[builder(setter(name = red))] u8,
+///     #[builder(setter(name = green))] u8,
+///     #[builder(default = green, setter(name = blue))] u8,
+/// );
+///
+/// assert!(Rgb::builder().red(1).green(2).blue(3).build() == Rgb(1, 2, 3));
+/// assert!(Rgb::builder().red(1).green(2).build() == Rgb(1, 2, 2));
+/// ```
+///
 /// # Customization with attributes
 ///
 /// In addition to putting `#[derive(TypedBuilder)]` on a type, you can specify a `#[builder(…)]`
@@ -90,6 +152,79 @@ use core::ops::FnOnce;
 ///     type is set, but `into` is specified, the return type will be generic and the user can
 ///     decide which type shall be constructed. In both cases an [`Into`] conversion is required to
 ///     be defined from the original type to the target type.
+///   - `try_into` or `try_into = ...`: like `into`, but with a [`TryInto`] conversion. The build
+///     method returns a `Result` with the conversion's error type, or with the type set by `error`
+///     when one is set - in which case the conversion error must be convertible into it with `?`.
+///
+///   - `validate = …` and `error = …`: check the built value before returning it. `validate` is
+///     a path to a function (or a closure) that receives a reference to the built value and returns
+///     `Result<(), E>`. The build method then returns `Result<T, Error>`, where `Error` is the type
+///     set with `error` and `T` is the built type (or the target of `into`). The validation error is
+///     converted into `Error` with `?`, so `Error` must implement `From<E>`.
+///
+///     ```
+///     use typed_builder::TypedBuilder;
+///
+///     #[derive(Debug, PartialEq, TypedBuilder)]
+///     #[builder(build_method(validate = Range::validate, error = String))]
+///     struct Range {
+///         start: u32,
+///         end: u32,
+///     }
+///
+///     impl Range {
+///         fn validate(&self) -> Result<(), String> {
+///             if self.start <= self.end {
+///                 Ok(())
+///             } else {
+///                 Err(format!("{} is after {}", self.start, self.end))
+///             }
+///         }
+///     }
+///
+///     assert_eq!(Range::builder().start(1).end(2).build(), Ok(Range { start: 1, end: 2 }));
+///     assert!(Range::builder().start(2).end(1).build().is_err());
+///     ```
+///
+///   - `context = …`: the type of the context that fields with `default_from_ctx` compute their
+///     defaults from. This adds a `build_with(&context)` method next to the build method (named
+///     after it - e.g. `finish_with` for `name = finish`), which behaves like it but resolves those
+///     defaults too.
+///
+///   - `async`: makes the build method an `async fn`, which awaits the defaults of `default_async`
+///     fields. It does not depend on any particular executor, and works in `no_std`. The builder
+///     then does not implement `FinishBuild`, so it can't be used with `nested` or `flatten`.
+///
+///   - `validate_async = …`: like `validate`, but the function returns a future of the `Result`,
+///     which the build method awaits. Requires `async` and `error`.
+///
+///     ```
+///     use typed_builder::TypedBuilder;
+///
+///     async fn resolve(host: &str) -> String {
+///         format!("{}:80", host)
+///     }
+///
+///     async fn check(server: &Server) -> Result<(), String> {
+///         if server.address.is_empty() {
+///             Err("empty address".to_owned())
+///         } else {
+///             Ok(())
+///         }
+///     }
+///
+///     #[derive(TypedBuilder)]
+///     #[builder(build_method(async, validate_async = check, error = String))]
+///     struct Server {
+///         host: String,
+///         #[builder(default_async = resolve(&host))]
+///         address: String,
+///     }
+///
+///     async fn create() -> Result<Server, String> {
+///         Server::builder().host("localhost".to_owned()).build().await
+///     }
+///     ```
 ///
 /// - `field_defaults(...)` is structured like the `#[builder(...)]` attribute you can put on the
 ///   fields and sets default options for fields of the type. If specific field need to revert some
@@ -122,18 +257,286 @@ use core::ops::FnOnce;
 ///    struct Point { x: f32, y: f32 }
 ///    ```
 ///
+/// - `into_builder`: generate `into_builder(self)` and `to_builder(&self)` methods on the type,
+///   which create a builder with all the fields already set to the values of the instance
+///   (`to_builder` requires the type to implement `Clone`). Since the fields are already set, only
+///   the setters marked `overridable` can be called on such builders to change these values -
+///   usually all of them, with `field_defaults(setter(overridable))`. Fields with `setter(skip)`
+///   are not kept - their default is used again when building. Not supported on enums or together
+///   with `flatten` fields.
+///
+///    ```
+///    use typed_builder::TypedBuilder;
+///
+///    #[derive(Clone, TypedBuilder)]
+///    #[builder(into_builder, field_defaults(setter(overridable)))]
+///    struct Config {
+///        host: String,
+///        port: u16,
+///    }
+///
+///    let config = Config::builder().host("localhost".to_owned()).port(80).build();
+///    let other = config.to_builder().port(8080).build();
+///    assert_eq!(other.host, "localhost");
+///    assert_eq!(other.port, 8080);
+///    ```
+///
+/// - `dynamic`: also generate a runtime-checked builder, named like the type with a `DynBuilder`
+///   suffix (`FooDynBuilder` for `Foo`). It is created with `new()` or `Default`, and its setters
+///   are the same as the typed builder's but take `&mut self` - so it can be filled in loops or
+///   from data. Its build method returns a `Result` whose error is a [`MissingFieldsError`]
+///   listing every required field that was not set. Not supported together with `flatten` fields.
+///
+///   The two builders can be converted into each other: `into_dyn()` turns a typed builder in any
+///   state into a runtime-checked one, and `try_into_typed::<S>()` turns it back into a typed
+///   builder in the state `S` - failing, and returning the runtime-checked builder, if `S` does
+///   not match which fields are set.
+///
+///    ```
+///    use typed_builder::TypedBuilder;
+///
+///    #[derive(Debug, TypedBuilder)]
+///    #[builder(dynamic)]
+///    struct Point {
+///        x: i32,
+///        y: i32,
+///        #[builder(default)]
+///        z: i32,
+///    }
+///
+///    let mut builder = PointDynBuilder::new();
+///    builder.x(1);
+///    let error = builder.build().unwrap_err();
+///    assert_eq!(error.missing_fields().collect::<Vec<_>>(), ["y"]);
+///
+///    let mut builder = PointDynBuilder::new();
+///    builder.x(1).y(2);
+///    let point = builder.build().unwrap();
+///    assert_eq!((point.x, point.y, point.z), (1, 2, 0));
+///
+///    // Let runtime code fill `y`, then finish with the typed builder.
+///    let mut builder = Point::builder().x(1).into_dyn();
+///    builder.y(2);
+///    let Ok(builder) = builder.try_into_typed::<((i32,), (i32,), ())>() else {
+///        panic!("`x` and `y` should be set");
+///    };
+///    let point = builder.z(3).build();
+///    assert_eq!((point.x, point.y, point.z), (1, 2, 3));
+///    ```
+///
+/// - `deserialize`: requires the `serde` feature. Generates a `<Type>Partial` alias (`FooPartial`
+///   for `Foo`) for the builder in the state where the fields with a default may already be set,
+///   and implements `serde::Deserialize` for it. Every field with a default is optional in the
+///   data, and falls back to its default when missing. The alias takes the states of the fields
+///   without a default as extra type parameters after the type's own generics, which default to
+///   `()` - leaving the field for its setter, so data that contains it fails to deserialize. Give
+///   a field's state as `(T,)` to read it from the data instead, which then must contain it -
+///   e.g. `ServerPartial<(String,)>` below.
+///   Fields are named as in the struct, or as given by `#[serde(rename = "...")]`, and also accept
+///   the names given by `#[serde(alias = "...")]`. Container attributes like
+///   `#[serde(rename_all = "...")]` are ignored. To let code replace a value read from the data,
+///   mark the field's setter `overridable`. Not supported together with `flatten` fields.
+///
+///    ```
+///    # #[cfg(feature = "serde")] {
+///    use typed_builder::TypedBuilder;
+///
+///    #[derive(TypedBuilder)]
+///    #[builder(deserialize)]
+///    struct Server {
+///        name: String,
+///        #[builder(default = 80)]
+///        #[serde(rename = "listen_port")]
+///        port: u16,
+///    }
+///
+///    let partial: ServerPartial = serde_json::from_str(r#"{"listen_port": 8080}"#).unwrap();
+///    let server = partial.name("api".to_owned()).build();
+///    assert_eq!(server.port, 8080);
+///
+///    let partial: ServerPartial<(String,)> = serde_json::from_str(r#"{"name": "api"}"#).unwrap();
+///    let server = partial.build();
+///    assert_eq!((server.name.as_str(), server.port), ("api", 80));
+///    # }
+///    ```
+///
+/// - `flattenable`: generate the `<Builder>Setters` trait that lets other builders hold this one
+///   in a `flatten` field (see the field's `flatten` below). Not supported on enums, on types with
+///   lifetime parameters, together with groups, `requires` or `conflicts_with`, or when the
+///   visibility of `builder_method` or `builder_type` is set.
+///
 /// On each **field**, the following values are permitted:
 ///
 /// - `default`: make the field optional, defaulting to `Default::default()`. This requires that
 ///   the field type implement `Default`. Mutually exclusive with any other form of default.
 ///
-/// - `default = …`: make the field optional, defaulting to the expression `…`.
+/// - `default = …`: make the field optional, defaulting to the expression `…`. The expression can
+///   refer to the values of other fields by name, regardless of the order they are declared in -
+///   the defaults are resolved after the fields they refer to. Defaults that refer to each other in
+///   a cycle are a compile error.
+///
+/// - `default_async = …`: make the field optional, defaulting to the output of the future `…`,
+///   which the build method awaits. Like `default`, it can refer to the values of other fields.
+///   Requires `#[builder(build_method(async))]` on the type.
+///
+/// - `default_from_ctx = |ctx: &Ctx| …`: make the field optional for the `build_with(&ctx)`
+///   method, which computes the default from the context passed to it. The context type must be
+///   set with `#[builder(build_method(context = Ctx))]` on the type. The plain `build()` method
+///   still exists, but requires the field to be set. Like with `default`, the closure can refer to
+///   other fields, and other defaults can refer to the field.
+///
+///    ```
+///    use typed_builder::TypedBuilder;
+///
+///    struct AppCtx {
+///        timeout: u32,
+///    }
+///
+///    #[derive(TypedBuilder)]
+///    #[builder(build_method(context = AppCtx))]
+///    struct Request {
+///        url: String,
+///        #[builder(default_from_ctx = |ctx: &AppCtx| ctx.timeout)]
+///        timeout: u32,
+///    }
+///
+///    let ctx = AppCtx { timeout: 30 };
+///    let request = Request::builder().url("/".to_owned()).build_with(&ctx);
+///    assert_eq!(request.timeout, 30);
+///    let request = Request::builder().url("/".to_owned()).timeout(5).build();
+///    assert_eq!(request.timeout, 5);
+///    ```
 ///
 /// - `default_code = "…"`: make the field optional, defaulting to the expression `…`. Note that
-///    you need to enclose it in quotes, which allows you to use it together with other custom
-///    derive proc-macro crates that complain about "expected literal".
-///    Note that if `...` contains a string, you can use raw string literals to avoid escaping the
-///    double quotes - e.g. `#[builder(default_code = r#""default text".to_owned()"#)]`.
+///   you need to enclose it in quotes, which allows you to use it together with other custom
+///   derive proc-macro crates that complain about "expected literal".
+///   Note that if `...` contains a string, you can use raw string literals to avoid escaping the
+///   double quotes - e.g. `#[builder(default_code = r#""default text".to_owned()"#)]`.
+///
+/// - `flatten`: for fields whose type derives `TypedBuilder` with `#[builder(flattenable)]`, this
+///   puts the setters of the field type's builder directly on this builder instead of giving the
+///   field a setter of its own. The required fields of the inner type must be set before `build`
+///   can be called. The setters come from a trait named after the inner builder with a `Setters`
+///   suffix (e.g. `CommonOptsBuilderSetters`), which must be in scope where they are called.
+///   Setters of this builder take precedence over flattened setters with the same name.
+///
+///   ```
+///   mod common {
+///       use typed_builder::TypedBuilder;
+///
+///       #[derive(TypedBuilder)]
+///       #[builder(flattenable)]
+///       pub struct CommonOpts {
+///           pub verbose: bool,
+///           #[builder(default = 3)]
+///           pub retries: u32,
+///       }
+///   }
+///
+///   use common::CommonOptsBuilderSetters;
+///   use typed_builder::TypedBuilder;
+///
+///   #[derive(TypedBuilder)]
+///   struct Server {
+///       port: u16,
+///       #[builder(flatten)]
+///       common: common::CommonOpts,
+///   }
+///
+///   let server = Server::builder().port(80).verbose(true).build();
+///   assert_eq!(server.common.retries, 3);
+///   ```
+///
+/// - `env = "VAR"`: requires the `std` feature. For fields with a default, lets the builder's
+///   `with_env()` method set the field by parsing the environment variable `VAR` with
+///   [`FromStr`](core::str::FromStr) - falling back to the default when the variable is not set.
+///   `with_env()` can be called while the `env` fields are unset, and returns a `Result` whose
+///   error, an `EnvError`, lists every variable that could not be parsed (or failed the setter's
+///   `validate`). The other fields are left for their setters. With `strip_option` the variable is
+///   parsed into the type inside the `Option`.
+///
+///    ```
+///    # #[cfg(feature = "std")] {
+///    use typed_builder::TypedBuilder;
+///
+///    #[derive(TypedBuilder)]
+///    struct Server {
+///        name: String,
+///        #[builder(default = 80, env = "APP_PORT")]
+///        port: u16,
+///    }
+///
+///    std::env::set_var("APP_PORT", "8080");
+///    let server = Server::builder().with_env().unwrap().name("api".to_owned()).build();
+///    assert_eq!(server.port, 8080);
+///    # }
+///    ```
+///
+/// - `group(name, exactly_one)` or `group(name, at_least_one)`: makes the field a member of the
+///   group `name`, whose fields must all have a default. For `exactly_one` groups, `build()` is
+///   only available once exactly one of the group's fields is set, and setting one of them makes
+///   the setters of the others unavailable. For `at_least_one` groups, `build()` is available once
+///   any of the group's fields is set, and the rest keep their defaults. The kind only needs to be
+///   given on one of the group's fields - the others can use `group(name)`. Groups can't be used
+///   with `into_builder`, `dynamic`, `deserialize` or `flattenable`.
+///
+///    ```
+///    use typed_builder::TypedBuilder;
+///
+///    #[derive(TypedBuilder)]
+///    struct Source {
+///        #[builder(default, setter(strip_option), group(source, exactly_one))]
+///        path: Option<String>,
+///        #[builder(default, setter(strip_option), group(source))]
+///        url: Option<String>,
+///    }
+///
+///    let source = Source::builder().url("http://example.com".to_owned()).build();
+///    assert_eq!(source.path, None);
+///    ```
+///
+///    ```
+///    use typed_builder::TypedBuilder;
+///
+///    #[derive(TypedBuilder)]
+///    struct Query {
+///        #[builder(default, setter(strip_option), group(filter, at_least_one))]
+///        name: Option<String>,
+///        #[builder(default, setter(strip_option), group(filter))]
+///        id: Option<u64>,
+///    }
+///
+///    let query = Query::builder().id(7).name("foo".to_owned()).build();
+///    assert_eq!(query.id, Some(7));
+///    ```
+///
+/// - `requires(field, ...)`: once this field is set, `build()` is only available when the given
+///   fields are set too.
+///
+/// - `conflicts_with(field, ...)`: setting this field makes the setters of the given fields
+///   unavailable, and vice versa.
+///
+///   Like groups, `requires` and `conflicts_with` can't be used with `maybe`, `env` or `flatten`
+///   fields, or with `into_builder`, `dynamic`, `deserialize` or `flattenable`.
+///
+///    ```
+///    use typed_builder::TypedBuilder;
+///
+///    #[derive(TypedBuilder)]
+///    struct Login {
+///        #[builder(default, setter(strip_option), requires(password))]
+///        username: Option<String>,
+///        #[builder(default, setter(strip_option))]
+///        password: Option<String>,
+///        #[builder(setter(strip_bool), conflicts_with(username))]
+///        anonymous: bool,
+///    }
+///
+///    let login = Login::builder().username("user".to_owned()).password("hunter2".to_owned()).build();
+///    assert!(!login.anonymous);
+///    let login = Login::builder().anonymous().build();
+///    assert_eq!(login.username, None);
+///    ```
 ///
 /// - `setter(...)`: settings for the field setters. The following values are permitted inside:
 ///
@@ -141,6 +544,9 @@ use core::ops::FnOnce;
 ///     of no value unless you enable docs for the builder type with `#[builder(doc)]` or similar on
 ///     the type.
 ///
+///   - `name = …`: sets the name of the setter, instead of the field's name. Prefixes and suffixes
+///     are still applied to it. For tuple structs this also names the field itself.
+///
 ///   - `skip`: do not define a method on the builder for this field. This requires that a default
 ///     be set.
 ///
@@ -151,16 +557,93 @@ use core::ops::FnOnce;
 ///   - `strip_option`: for `Option<...>` fields only, this makes the setter wrap its argument with
 ///     `Some(...)`, relieving the caller from having to do this. Note that with this setting on
 ///     one cannot set the field to `None` with the setter - so the only way to get it to be `None`
-///     is by using `#[builder(default)]` and not calling the field's setter.
+///     is by using `#[builder(default)]` and not calling the field's setter - or by using
+///     `strip_option(fallback = name)`, which generates an additional setter with the given name
+///     that accepts the full `Option`.
 ///
 ///   - `strip_bool`: for `bool` fields only, this makes the setter receive no arguments and simply
 ///     set the field's value to `true`. When used, the `default` is automatically set to `false`.
 ///
+///   - `nested`: for fields whose type also derives `TypedBuilder`, this makes the setter accept a
+///     closure that receives the field type's builder and returns it with its fields set. The
+///     setter builds the field's value itself, so `.inner(|b| b.a(1))` is the same as
+///     `.inner(Inner::builder().a(1).build())`. Forgetting to set a required field of the inner
+///     builder is still a compile error. The field type's builder must not have its
+///     `builder_method`/`builder_type` visibility changed, and its build method must be public and
+///     infallible, without `into`.
+///
 ///   - `transform = |param1: Type1, param2: Type2 ...| expr`: this makes the setter accept
 ///     `param1: Type1, param2: Type2 ...` instead of the field type itself. The parameters are
 ///     transformed into the field type using the expression `expr`. The transformation is performed
 ///     when the setter is called.
 ///
+///   - `try_transform = |param1: Type1, ...| -> Result<T, E> { expr }`: like `transform`, but the
+///     closure is fallible - the setter returns `Result<Builder, E>`, with the field marked as set
+///     only when the closure returns `Ok`. Unlike `transform`, the closure must declare its return
+///     type, since the setter's error type can't be named otherwise: `|s: &str| s.parse::<u16>()`
+///     is rejected, and has to be written as `|s: &str| -> Result<u16, ParseIntError> { s.parse() }`.
+///     The return type can also be an alias of `Result`, like `io::Result<T>`.
+///
+///   - `each = "item"`: generates an additional setter named `item` that adds a single item to the
+///     collection (e.g. `Vec`, `HashSet`) each time it is called. The collection must implement
+///     `IntoIterator` and `Extend` of its `IntoIterator::Item`, which the item setter accepts. The
+///     collection starts from the field's `default` - `Default::default()` if none is given - and
+///     the field is implicitly `default` so the item setter can be called any number of times -
+///     including zero. If the item setter has the same name as the field's setter, it replaces it.
+///     Use `each(name = "item", into)` to make the item setter accept `Into` values. For maps, the
+///     item setter accepts a `(key, value)` tuple by default - it only takes the key and the value
+///     as separate arguments with `each(name = "item", key_value)`, which takes their types from the
+///     first two type arguments of the field's type (e.g. `K` and `V` for `HashMap<K, V, S>`). Can't
+///     be combined with `transform`, `try_transform`, `validate` or `default_async`, or with a
+///     `default` that refers to other fields.
+///
+///   - `overridable`: allows calling the setter even when the field is already set, replacing the
+///     previous value. Without it, setting a field twice is a compile error. Can also be set for
+///     all fields with `field_defaults(setter(overridable))`.
+///
+///   - `reset`: for fields with a default, generates a `reset_<field>()` method on the builder,
+///     which drops the field's value (if it was set) so that the default will be used unless the
+///     field is set again. Use `reset = name` to choose the name of the method.
+///
+///   - `maybe`: for fields with a default, generates a `maybe_<field>()` setter that accepts an
+///     `Option` of what the regular setter accepts. When given `None`, the field's default is used,
+///     so callers holding an `Option` don't need to branch on it to set the field. Use
+///     `maybe = name` to choose the name of the setter.
+///
+///   - `from_str`: lets the `set_by_name` method of the runtime-checked builder (see `dynamic`)
+///     set the field, by parsing a string with [`FromStr`](core::str::FromStr). The field is
+///     looked up by its setter's name, including any `prefix` or `suffix`. With `strip_option`
+///     the string is parsed into the type inside the `Option`. `set_by_name` fails with a
+///     [`SetByNameError`] naming the unknown setter or the field whose value could not be parsed.
+///
+///     ```
+///     use typed_builder::{SetByNameError, TypedBuilder};
+///
+///     #[derive(Debug, TypedBuilder)]
+///     #[builder(dynamic)]
+///     struct Server {
+///         #[builder(setter(from_str))]
+///         host: String,
+///         #[builder(default = 80, setter(from_str))]
+///         port: u16,
+///     }
+///
+///     let mut builder = ServerDynBuilder::new();
+///     for (key, value) in [("host", "localhost"), ("port", "8080")] {
+///         builder.set_by_name(key, value).unwrap();
+///     }
+///     assert_eq!(builder.set_by_name("timeout", "1").err(), Some(SetByNameError::UnknownField("timeout")));
+///     assert_eq!(builder.set_by_name("port", "http").err(), Some(SetByNameError::InvalidValue { field: "port" }));
+///     let server = builder.build().unwrap();
+///     assert_eq!((server.host.as_str(), server.port), ("localhost", 8080));
+///     ```
+///
+///   - `validate = …`: a closure or a path to a function that receives a reference to the value
+///     the setter is about to store and returns `bool`. The setter then returns a `Result`, which
+///     is a [`ValidationError`] holding the field's name when the validation fails. With
+///     `strip_option` the validation receives the value before it is wrapped in `Some(...)`, and with
+///     `into` and `transform` it receives the converted value.
+///
 ///   - `prefix = "..."` prepends the setter method with the specified prefix. For example, setting
 ///     `prefix = "with_"` results in setters like `with_x` or `with_y`. This option is combinable
 ///     with `suffix = "..."`.
@@ -187,6 +670,269 @@ impl<T> Optional<T> for (T,) {
     }
 }
 
+/// The `Result` types `try_transform` closures can return, for finding the error type of the
+/// setter when the closure returns an alias like `io::Result<T>`.
+#[doc(hidden)]
+pub trait TransformResult {
+    type Error;
+}
+
+impl<T, E> TransformResult for Result<T, E> {
+    type Error = E;
+}
+
+/// The state of a field set with a `maybe_` setter - which falls back to the field's default if it
+/// was given `None`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Maybe<T>(pub Option<T>);
+
+impl<T> Optional<T> for Maybe<T> {
+    fn into_value<F: FnOnce() -> T>(self, default: F) -> T {
+        self.0.unwrap_or_else(default)
+    }
+}
+
+/// The states a field of a typed builder can be in, converted to and from the `Option` that
+/// runtime-checked builders (`#[builder(dynamic)]`) store for it.
+#[doc(hidden)]
+pub trait FieldState<T>: Sized {
+    fn into_option(self) -> Option<T>;
+
+    /// Fails, returning the value back, if it does not fit the state.
+    fn try_from_option(value: Option<T>) -> Result<Self, Option<T>>;
+}
+
+impl<T> FieldState<T> for () {
+    fn into_option(self) -> Option<T> {
+        None
+    }
+
+    fn try_from_option(value: Option<T>) -> Result<Self, Option<T>> {
+        match value {
+            None => Ok(()),
+            Some(value) => Err(Some(value)),
+        }
+    }
+}
+
+impl<T> FieldState<T> for (T,) {
+    fn into_option(self) -> Option<T> {
+        Some(self.0)
+    }
+
+    fn try_from_option(value: Option<T>) -> Result<Self, Option<T>> {
+        match value {
+            Some(value) => Ok((value,)),
+            None => Err(None),
+        }
+    }
+}
+
+impl<T> FieldState<T> for Maybe<T> {
+    fn into_option(self) -> Option<T> {
+        self.0
+    }
+
+    fn try_from_option(value: Option<T>) -> Result<Self, Option<T>> {
+        Ok(Maybe(value))
+    }
+}
+
+/// Implemented by `#[derive(TypedBuilder)]` for structs, so that other builders can create their
+/// builders - e.g. for `#[builder(setter(nested))]`.
+///
+/// Not implemented when `builder_method(vis = ...)` or `builder_type(vis = ...)` are set, since
+/// that would expose the builder outside of its intended visibility.
+pub trait HasTypedBuilder {
+    /// The type of the builder, in the state where none of its fields are set.
+    type Builder;
+
+    /// Create a builder for this type. Same as calling the builder method.
+    fn create_builder() -> Self::Builder;
+}
+
+/// Implemented for builders in states that allow building a `T` - i.e. when all their required
+/// fields are set.
+///
+/// Only implemented when the build method is public and returns the built type itself, without
+/// `into`, `try_into` or `validate`.
+pub trait FinishBuild<T> {
+    /// Build the value. Same as calling the build method.
+    fn finish_build(self) -> T;
+}
+
+/// Implemented for types with `#[builder(flattenable)]`, which can be used as the type of
+/// `#[builder(flatten)]` fields.
+pub trait Flattenable: HasTypedBuilder {}
+
+/// Implemented for builders that have a `#[builder(flatten)]` field of type `T`, to let the
+/// setters of `T`'s builder work on the inner builder they hold.
+pub trait FlattenHost<T> {
+    /// The builder of `T` currently held by this builder.
+    type Inner;
+
+    /// This builder, with the builder of `T` replaced by a `B`.
+    type Replaced<B>;
+
+    /// Replace the held builder of `T` with the result of `f`.
+    fn flatten_map<B>(self, f: impl FnOnce(Self::Inner) -> B) -> Self::Replaced<B>;
+
+    /// Like [`flatten_map`](FlattenHost::flatten_map), for fallible setters.
+    fn flatten_try_map<B, E>(self, f: impl FnOnce(Self::Inner) -> Result<B, E>) -> Result<Self::Replaced<B>, E>;
+}
+
+/// Returned by the setters of fields with `#[builder(setter(validate = ...))]` when the value
+/// passed to them fails the validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The name of the field the invalid value was passed for.
+    pub field: &'static str,
+}
+
+impl core::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid value for field `{}`", self.field)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+/// Returned by the build method of the builders generated with `#[builder(dynamic)]` when some of
+/// the required fields were not set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingFieldsError {
+    required_fields: &'static [&'static str],
+    missing: u64,
+}
+
+impl MissingFieldsError {
+    #[doc(hidden)]
+    pub const fn new(required_fields: &'static [&'static str], missing: u64) -> Self {
+        Self {
+            required_fields,
+            missing,
+        }
+    }
+
+    /// The names of the required fields that were not set, in declaration order.
+    pub fn missing_fields(&self) -> impl Iterator<Item = &'static str> {
+        let missing = self.missing;
+        self.required_fields
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| missing & (1 << i) != 0)
+            .map(|(_, name)| *name)
+    }
+}
+
+impl core::fmt::Display for MissingFieldsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "missing required fields:")?;
+        for (i, name) in self.missing_fields().enumerate() {
+            if i == 0 {
+                write!(f, " `{}`", name)?;
+            } else {
+                write!(f, ", `{}`", name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MissingFieldsError {}
+
+/// Returned by the `set_by_name` method of the builders generated with `#[builder(dynamic)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetByNameError<'a> {
+    /// No field with a `setter(from_str)` has a setter with this name.
+    UnknownField(&'a str),
+    /// The value could not be parsed, or failed the field's `setter(validate = ...)`.
+    InvalidValue {
+        /// The name of the field the invalid value was passed for.
+        field: &'static str,
+    },
+}
+
+impl core::fmt::Display for SetByNameError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SetByNameError::UnknownField(name) => write!(f, "unknown field `{}`", name),
+            SetByNameError::InvalidValue { field } => write!(f, "invalid value for field `{}`", field),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SetByNameError<'_> {}
+
+/// Returned by the `with_env` method of builders with `#[builder(env = "...")]` fields when some of
+/// the environment variables could not be parsed.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvError {
+    variables: std::vec::Vec<&'static str>,
+}
+
+#[cfg(feature = "std")]
+impl EnvError {
+    /// The names of the environment variables that could not be parsed, in declaration order.
+    pub fn variables(&self) -> &[&'static str] {
+        &self.variables
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for EnvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid environment variables:")?;
+        for (i, name) in self.variables.iter().enumerate() {
+            if i == 0 {
+                write!(f, " `{}`", name)?;
+            } else {
+                write!(f, ", `{}`", name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EnvError {}
+
+/// Reads the fields of `with_env`, collecting the variables that fail.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[derive(Default)]
+pub struct EnvReader {
+    invalid: std::vec::Vec<&'static str>,
+}
+
+#[cfg(feature = "std")]
+impl EnvReader {
+    pub fn read<T: core::str::FromStr>(&mut self, variable: &'static str, validate: impl FnOnce(&T) -> bool) -> Option<T> {
+        let value = match std::env::var(variable) {
+            Ok(value) => value.parse().ok().filter(validate),
+            Err(std::env::VarError::NotPresent) => return None,
+            Err(std::env::VarError::NotUnicode(_)) => None,
+        };
+        if value.is_none() {
+            self.invalid.push(variable);
+        }
+        value
+    }
+
+    pub fn finish(self) -> Result<(), EnvError> {
+        if self.invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(EnvError { variables: self.invalid })
+        }
+    }
+}
+
 // It'd be nice for the compilation tests to live in tests/ with the rest, but short of pulling in
 // some other test runner for that purpose (e.g. compiletest_rs), rustdoc compile_fail in this
 // crate is all we can use.
@@ -268,6 +1014,115 @@ impl<T> Optional<T> for (T,) {
 /// let _ = Foo::builder().x(Uncloneable).clone();
 /// ```
 ///
+/// `validate` without `error`:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// #[builder(build_method(validate = |_: &Foo| Ok::<(), ()>(())))]
+/// struct Foo {
+///     x: i32,
+/// }
+/// ```
+///
+/// `try_transform` without a declared return type:
+/// (“try_transform closure must declare its `Result` return type, e.g. …”)
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(setter(try_transform = |s: &str| s.parse::<u16>()))]
+///     port: u16,
+/// }
+/// ```
+///
+/// Missing required field in a `nested` setter:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Inner {
+///     a: i32,
+///     b: i32,
+/// }
+///
+/// #[derive(TypedBuilder)]
+/// struct Outer {
+///     #[builder(setter(nested))]
+///     inner: Inner,
+/// }
+///
+/// Outer::builder().inner(|b| b.a(1)).build();
+/// ```
+///
+/// Missing required field of a `flatten` field:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// #[builder(flattenable)]
+/// struct Inner {
+///     a: i32,
+/// }
+///
+/// #[derive(TypedBuilder)]
+/// struct Outer {
+///     b: i32,
+///     #[builder(flatten)]
+///     inner: Inner,
+/// }
+///
+/// Outer::builder().b(1).build();
+/// ```
+///
+/// Setters of `into_builder` builders are not overridable by default:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// #[builder(into_builder)]
+/// struct Foo {
+///     x: i32,
+/// }
+///
+/// Foo::builder().x(1).build().into_builder().x(2).build();
+/// ```
+///
+/// Flattening a type without `#[builder(flattenable)]`:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Inner {
+///     a: i32,
+/// }
+///
+/// #[derive(TypedBuilder)]
+/// struct Outer {
+///     #[builder(flatten)]
+///     inner: Inner,
+/// }
+/// ```
+///
+/// `flattenable` types can't have lifetime parameters:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// #[builder(flattenable)]
+/// struct Inner<'a> {
+///     a: &'a str,
+/// }
+/// ```
+///
 /// Handling deprecated fields:
 ///
 /// ```compile_fail
@@ -283,4 +1138,141 @@ impl<T> Optional<T> for (T,) {
 /// #[deny(deprecated)]
 /// Foo::builder().value(42).build();
 ///```
+///
+/// Fields of an `exactly_one` group can't both be set:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(default, group(source, exactly_one))]
+///     path: Option<String>,
+///     #[builder(default, group(source))]
+///     url: Option<String>,
+/// }
+///
+/// Foo::builder().path(None).url(None).build();
+/// ```
+///
+/// Nor can none of them be set, which picks a deprecated overload of `build` to explain why:
+/// (“use of deprecated method `FooBuilder::build`: Missing one of: path, url”)
+///
+/// ```compile_fail,E0061
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(default, group(source, exactly_one))]
+///     path: Option<String>,
+///     #[builder(default, group(source))]
+///     url: Option<String>,
+/// }
+///
+/// Foo::builder().build();
+/// ```
+///
+/// An `at_least_one` group needs one of its fields set too:
+/// (“use of deprecated method `FooBuilder::build`: Missing one of: name, id”)
+///
+/// ```compile_fail,E0061
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(default, group(filter, at_least_one))]
+///     name: Option<String>,
+///     #[builder(default, group(filter))]
+///     id: Option<u64>,
+/// }
+///
+/// Foo::builder().build();
+/// ```
+///
+/// A field's `requires` must be set before building:
+/// (“use of deprecated method `FooBuilder::<…>::build`: Missing field password - required by username”)
+///
+/// ```compile_fail,E0061
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(default, requires(password))]
+///     username: Option<String>,
+///     #[builder(default)]
+///     password: Option<String>,
+/// }
+///
+/// Foo::builder().username(None).build();
+/// ```
+///
+/// And `conflicts_with` hides the other field's setter:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(default)]
+///     username: Option<String>,
+///     #[builder(setter(strip_bool), conflicts_with(username))]
+///     anonymous: bool,
+/// }
+///
+/// Foo::builder().anonymous().username(None).build();
+/// ```
+///
+/// Defaults can't depend on each other in a cycle:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(default = b + 1)]
+///     a: u32,
+///     #[builder(default = a + 1)]
+///     b: u32,
+/// }
+/// ```
+///
+/// Async defaults need an async build method:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// async fn answer() -> u32 {
+///     42
+/// }
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(default_async = answer())]
+///     x: u32,
+/// }
+/// ```
+///
+/// Item setters skip the field's `validate`, so the two can't be combined:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(setter(each = "item", validate = |v: &Vec<u32>| !v.is_empty()))]
+///     items: Vec<u32>,
+/// }
+/// ```
+///
+/// `key_value` needs key and value type arguments:
+///
+/// ```compile_fail
+/// use typed_builder::TypedBuilder;
+///
+/// #[derive(TypedBuilder)]
+/// struct Foo {
+///     #[builder(setter(each(name = item, key_value)))]
+///     items: Vec<(u32, u32)>,
+/// }
+/// ```
 fn _compile_fail_tests() {}
@@ -1,6 +1,8 @@
 #![warn(clippy::pedantic)]
 #![no_std]
 
+extern crate alloc;
+
 use typed_builder::TypedBuilder;
 
 #[test]
@@ -155,3 +157,22 @@ fn test_field_dependencies_in_build() {
             }
     );
 }
+
+#[test]
+fn test_setter_each() {
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+
+    #[derive(PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(each = "item"))]
+        items: Vec<i32>,
+        #[builder(setter(each(name = entry, key_value)))]
+        entries: BTreeMap<i32, i32>,
+    }
+
+    let foo = Foo::builder().item(1).entry(1, 2).item(2).build();
+    assert!(foo.items == [1, 2]);
+    assert!(foo.entries.into_iter().eq([(1, 2)]));
+    assert!(Foo::builder().build().items.is_empty());
+}
@@ -364,7 +364,7 @@ fn test_builder_type_stability_with_other_generics() {
 }
 
 #[test]
-#[allow(clippy::items_after_statements)]
+#[allow(clippy::items_after_statements, clippy::type_complexity)]
 fn test_builder_type_with_default_on_generic_type() {
     #[derive(PartialEq, TypedBuilder)]
     struct Types<X, Y = ()> {
@@ -530,6 +530,7 @@ fn test_clone_builder() {
 #[test]
 #[allow(clippy::items_after_statements)]
 fn test_clone_builder_with_generics() {
+    #[allow(dead_code)]
     #[derive(PartialEq, Default)]
     struct Uncloneable;
 
@@ -751,7 +752,7 @@ fn test_prefix() {
     }
 
     let foo = Foo::builder().with_x(1).with_y(2).build();
-    assert_eq!(foo, Foo { x: 1, y: 2 })
+    assert_eq!(foo, Foo { x: 1, y: 2 });
 }
 
 #[test]
@@ -764,7 +765,7 @@ fn test_suffix() {
     }
 
     let foo = Foo::builder().x_value(1).y_value(2).build();
-    assert_eq!(foo, Foo { x: 1, y: 2 })
+    assert_eq!(foo, Foo { x: 1, y: 2 });
 }
 
 #[test]
@@ -777,7 +778,7 @@ fn test_prefix_and_suffix() {
     }
 
     let foo = Foo::builder().with_x_value(1).with_y_value(2).build();
-    assert_eq!(foo, Foo { x: 1, y: 2 })
+    assert_eq!(foo, Foo { x: 1, y: 2 });
 }
 
 #[test]
@@ -800,3 +801,1379 @@ fn test_issue_118() {
 
     let _ = Foo::<u32>::builder().build();
 }
+
+#[test]
+fn test_enum_variants() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(field_defaults(setter(into)))]
+    enum Event {
+        Click {
+            x: i32,
+            y: i32,
+            #[builder(default = 1)]
+            button: u8,
+        },
+        #[builder(builder_method(name = key), builder_type(name = KeyEventBuilder))]
+        KeyPress {
+            key: char,
+            #[builder(default)]
+            modifiers: Vec<String>,
+        },
+        #[allow(dead_code)]
+        Resize(u32, u32),
+        #[allow(dead_code)]
+        Close,
+    }
+
+    assert_eq!(
+        Event::click_builder().x(1).y(2).build(),
+        Event::Click { x: 1, y: 2, button: 1 }
+    );
+    assert_eq!(
+        Event::click_builder().button(3).y(2_i16).x(1_i8).build(),
+        Event::Click { x: 1, y: 2, button: 3 }
+    );
+
+    let builder: KeyEventBuilder<_> = Event::key();
+    assert_eq!(
+        builder.key('a').build(),
+        Event::KeyPress {
+            key: 'a',
+            modifiers: Vec::new()
+        }
+    );
+}
+
+#[test]
+fn test_enum_variants_with_generics() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    enum Shape<T> {
+        Circle { radius: T },
+        Rect { width: T, height: T },
+    }
+
+    assert_eq!(Shape::circle_builder().radius(1.5).build(), Shape::Circle { radius: 1.5 });
+    assert_eq!(
+        Shape::rect_builder().width(2).height(3).build(),
+        Shape::Rect { width: 2, height: 3 }
+    );
+}
+
+#[test]
+fn test_tuple_struct() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Rgb(u8, u8, #[builder(default = _1)] u8);
+
+    assert_eq!(Rgb::builder()._0(1)._1(2)._2(3).build(), Rgb(1, 2, 3));
+    assert_eq!(Rgb::builder()._1(2)._0(1).build(), Rgb(1, 2, 2));
+}
+
+#[test]
+fn test_tuple_struct_setter_name() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(field_defaults(setter(prefix = "with_")))]
+    struct Rgb(
+        #[builder(setter(name = red))] u8,
+        #[builder(setter(name = green))] u8,
+        #[builder(default = red, setter(name = blue))] u8,
+    );
+
+    assert_eq!(Rgb::builder().with_red(1).with_green(2).with_blue(3).build(), Rgb(1, 2, 3));
+    assert_eq!(Rgb::builder().with_green(2).with_red(1).build(), Rgb(1, 2, 1));
+}
+
+#[test]
+fn test_tuple_struct_with_generics() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Wrapper<T>(#[builder(setter(into))] T);
+
+    assert_eq!(Wrapper::<String>::builder()._0("hello").build(), Wrapper("hello".to_owned()));
+}
+
+#[test]
+fn test_setter_name_on_named_field() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(name = first))]
+        x: i32,
+        #[builder(default = x + 1)]
+        y: i32,
+    }
+
+    assert_eq!(Foo::builder().first(1).build(), Foo { x: 1, y: 2 });
+}
+
+#[test]
+fn test_build_method_validate() {
+    #[derive(Debug, PartialEq)]
+    enum RangeError {
+        Empty,
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(build_method(validate = Range::validate, error = RangeError))]
+    struct Range {
+        start: u32,
+        #[builder(default = start + 1)]
+        end: u32,
+    }
+
+    impl Range {
+        fn validate(&self) -> Result<(), RangeError> {
+            if self.start < self.end {
+                Ok(())
+            } else {
+                Err(RangeError::Empty)
+            }
+        }
+    }
+
+    assert_eq!(Range::builder().start(1).build(), Ok(Range { start: 1, end: 2 }));
+    assert_eq!(Range::builder().start(1).end(5).build(), Ok(Range { start: 1, end: 5 }));
+    assert_eq!(Range::builder().start(5).end(5).build(), Err(RangeError::Empty));
+}
+
+#[test]
+fn test_build_method_validate_with_closure_and_into() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(build_method(
+        into = Port,
+        validate = |foo: &Foo| if foo.port == 0 { Err("port must not be 0") } else { Ok(()) },
+        error = String,
+    ))]
+    struct Foo {
+        port: u16,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Port(u16);
+
+    impl From<Foo> for Port {
+        fn from(foo: Foo) -> Self {
+            Self(foo.port)
+        }
+    }
+
+    assert_eq!(Foo::builder().port(80).build(), Ok(Port(80)));
+    assert_eq!(Foo::builder().port(0).build(), Err("port must not be 0".to_owned()));
+}
+
+#[test]
+fn test_build_method_try_into_specific_type() {
+    #[derive(TypedBuilder)]
+    #[builder(build_method(try_into = Port))]
+    struct RawPort {
+        port: u32,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Port(u16);
+
+    impl TryFrom<RawPort> for Port {
+        type Error = core::num::TryFromIntError;
+
+        fn try_from(raw: RawPort) -> Result<Self, Self::Error> {
+            Ok(Self(raw.port.try_into()?))
+        }
+    }
+
+    assert_eq!(RawPort::builder().port(80).build(), Ok(Port(80)));
+    assert!(RawPort::builder().port(100_000).build().is_err());
+}
+
+#[test]
+fn test_build_method_try_into_generic() {
+    #[derive(TypedBuilder)]
+    #[builder(build_method(try_into))]
+    struct RawPort {
+        port: u32,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Port(u16);
+
+    impl TryFrom<RawPort> for Port {
+        type Error = &'static str;
+
+        fn try_from(raw: RawPort) -> Result<Self, Self::Error> {
+            raw.port.try_into().map(Self).map_err(|_| "port out of range")
+        }
+    }
+
+    let port: Result<Port, _> = RawPort::builder().port(80).build();
+    assert_eq!(port, Ok(Port(80)));
+    assert_eq!(RawPort::builder().port(100_000).build::<Port>(), Err("port out of range"));
+}
+
+#[test]
+fn test_build_method_try_into_with_validate() {
+    #[derive(Debug, PartialEq)]
+    enum PortError {
+        Reserved,
+        OutOfRange,
+    }
+
+    impl From<core::num::TryFromIntError> for PortError {
+        fn from(_: core::num::TryFromIntError) -> Self {
+            Self::OutOfRange
+        }
+    }
+
+    #[derive(TypedBuilder)]
+    #[builder(build_method(
+        try_into,
+        validate = |raw: &RawPort| if raw.port < 1024 { Err(PortError::Reserved) } else { Ok(()) },
+        error = PortError,
+    ))]
+    struct RawPort {
+        port: u32,
+    }
+
+    impl TryFrom<RawPort> for u16 {
+        type Error = core::num::TryFromIntError;
+
+        fn try_from(raw: RawPort) -> Result<Self, Self::Error> {
+            raw.port.try_into()
+        }
+    }
+
+    assert_eq!(RawPort::builder().port(8080).build::<u16>(), Ok(8080));
+    assert_eq!(RawPort::builder().port(80).build::<u16>(), Err(PortError::Reserved));
+    assert_eq!(RawPort::builder().port(100_000).build::<u16>(), Err(PortError::OutOfRange));
+}
+
+#[test]
+fn test_setter_validate() {
+    fn is_short(value: &str) -> bool {
+        value.len() < 4
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(validate = |v: &u16| *v > 0))]
+        port: u16,
+        #[builder(default, setter(into, strip_option, validate = is_short))]
+        name: Option<String>,
+    }
+
+    assert_eq!(
+        Foo::builder().port(80).unwrap().name("foo").unwrap().build(),
+        Foo {
+            port: 80,
+            name: Some("foo".to_owned())
+        }
+    );
+    assert_eq!(
+        Foo::builder().port(0).err(),
+        Some(typed_builder::ValidationError { field: "port" })
+    );
+    assert_eq!(
+        Foo::builder().port(80).unwrap().name("foobar").err().unwrap().to_string(),
+        "invalid value for field `name`"
+    );
+}
+
+#[test]
+fn test_setter_try_transform() {
+    use core::num::ParseIntError;
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(try_transform = |s: &str| -> Result<u16, ParseIntError> { s.parse() }))]
+        port: u16,
+        #[builder(default, setter(try_transform = |s: &str| -> Result<Option<u32>, ParseIntError> { s.parse().map(Some) }))]
+        timeout: Option<u32>,
+    }
+
+    assert_eq!(
+        Foo::builder().port("8080").unwrap().build(),
+        Foo {
+            port: 8080,
+            timeout: None
+        }
+    );
+    assert_eq!(
+        Foo::builder().timeout("30").unwrap().port("80").unwrap().build(),
+        Foo {
+            port: 80,
+            timeout: Some(30)
+        }
+    );
+    assert!(Foo::builder().port("eighty").is_err());
+}
+
+#[test]
+fn test_setter_try_transform_with_result_alias() {
+    use core::num::ParseIntError;
+
+    type ParseResult<T> = Result<T, ParseIntError>;
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(try_transform = |s: &str| -> ParseResult<u16> { s.parse() }))]
+        port: u16,
+        #[builder(default, setter(try_transform = |n: u32| -> std::io::Result<u32> { Ok(n) }))]
+        timeout: u32,
+    }
+
+    assert_eq!(
+        Foo::builder().timeout(30).unwrap().port("8080").unwrap().build(),
+        Foo { port: 8080, timeout: 30 }
+    );
+    let error: Option<ParseIntError> = Foo::builder().port("eighty").err();
+    assert_eq!(error, "eighty".parse::<u16>().err());
+}
+
+#[test]
+fn test_setter_each() {
+    use std::collections::{hash_map::RandomState, BTreeMap, BTreeSet, HashMap};
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Command {
+        program: String,
+        #[builder(setter(each = "arg"))]
+        args: Vec<String>,
+        #[builder(setter(each(name = header, into, key_value)))]
+        headers: BTreeMap<String, String>,
+        #[builder(default = BTreeSet::from([1]), setter(each = "tags"))]
+        tags: BTreeSet<u32>,
+        #[builder(setter(each(name = env, key_value)))]
+        envs: HashMap<&'static str, &'static str, RandomState>,
+    }
+
+    assert_eq!(
+        Command::builder().program("ls".to_owned()).build(),
+        Command {
+            program: "ls".to_owned(),
+            args: Vec::new(),
+            headers: BTreeMap::new(),
+            tags: BTreeSet::from([1]),
+            envs: HashMap::default(),
+        }
+    );
+    assert_eq!(
+        Command::builder()
+            .arg("-l".to_owned())
+            .program("ls".to_owned())
+            .header("a", "b")
+            .arg("-a".to_owned())
+            .tags(2)
+            .tags(3)
+            .env("LANG", "C")
+            .build(),
+        Command {
+            program: "ls".to_owned(),
+            args: vec!["-l".to_owned(), "-a".to_owned()],
+            headers: BTreeMap::from([("a".to_owned(), "b".to_owned())]),
+            tags: BTreeSet::from([1, 2, 3]),
+            envs: HashMap::from_iter([("LANG", "C")]),
+        }
+    );
+    assert_eq!(
+        Command::builder()
+            .program("ls".to_owned())
+            .args(vec!["-l".to_owned()])
+            .arg("-a".to_owned())
+            .build()
+            .args,
+        vec!["-l".to_owned(), "-a".to_owned()]
+    );
+}
+
+#[test]
+fn test_setter_each_item_type_from_collection() {
+    trait Array {
+        type Item;
+    }
+
+    impl<T, const N: usize> Array for [T; N] {
+        type Item = T;
+    }
+
+    // The type argument of the collection is not its item type.
+    #[derive(Debug, PartialEq)]
+    struct SmallVec<A: Array>(Vec<A::Item>);
+
+    impl<A: Array> Default for SmallVec<A> {
+        fn default() -> Self {
+            Self(Vec::new())
+        }
+    }
+
+    impl<A: Array> Extend<A::Item> for SmallVec<A> {
+        fn extend<I: IntoIterator<Item = A::Item>>(&mut self, iter: I) {
+            self.0.extend(iter);
+        }
+    }
+
+    impl<A: Array> IntoIterator for SmallVec<A> {
+        type Item = A::Item;
+        type IntoIter = std::vec::IntoIter<A::Item>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.into_iter()
+        }
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(each(name = item, into)))]
+        items: SmallVec<[u32; 4]>,
+    }
+
+    assert_eq!(Foo::builder().item(1u8).item(2u32).build().items.0, vec![1, 2]);
+}
+
+#[test]
+fn test_setter_nested() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Inner<T: Default> {
+        a: T,
+        #[builder(default)]
+        b: T,
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Outer {
+        #[builder(setter(nested))]
+        inner: Inner<i32>,
+        #[builder(default = Inner { a: 1, b: 2 }, setter(nested))]
+        other: Inner<i32>,
+    }
+
+    assert_eq!(
+        Outer::builder().inner(|b| b.a(1)).build(),
+        Outer {
+            inner: Inner { a: 1, b: 0 },
+            other: Inner { a: 1, b: 2 },
+        }
+    );
+    assert_eq!(
+        Outer::builder().other(|b| b.b(4).a(3)).inner(|b| b.a(1).b(2)).build(),
+        Outer {
+            inner: Inner { a: 1, b: 2 },
+            other: Inner { a: 3, b: 4 },
+        }
+    );
+}
+
+#[test]
+fn test_flatten() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(flattenable)]
+    struct CommonOpts {
+        verbose: bool,
+        #[builder(default = 3, setter(validate = |retries: &u8| *retries < 10))]
+        retries: u8,
+        #[builder(default, setter(strip_option))]
+        name: Option<String>,
+        #[builder(setter(each = "tag"))]
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Server {
+        port: u16,
+        #[builder(flatten)]
+        common: CommonOpts,
+    }
+
+    assert_eq!(
+        Server::builder().verbose(true).port(80).build(),
+        Server {
+            port: 80,
+            common: CommonOpts {
+                verbose: true,
+                retries: 3,
+                name: None,
+                tags: Vec::new(),
+            },
+        }
+    );
+    assert_eq!(
+        Server::builder()
+            .tag("a".to_owned())
+            .name("server".to_owned())
+            .port(80)
+            .retries(5)
+            .unwrap()
+            .verbose(false)
+            .tag("b".to_owned())
+            .build(),
+        Server {
+            port: 80,
+            common: CommonOpts {
+                verbose: false,
+                retries: 5,
+                name: Some("server".to_owned()),
+                tags: vec!["a".to_owned(), "b".to_owned()],
+            },
+        }
+    );
+    assert!(Server::builder().retries(10).is_err());
+}
+
+#[test]
+fn test_flatten_with_generics() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(flattenable)]
+    struct Inner<T> {
+        #[builder(setter(into))]
+        value: T,
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Outer<T, U> {
+        #[builder(flatten)]
+        first: Inner<T>,
+        second: U,
+    }
+
+    assert_eq!(
+        Outer::builder().second(2).value(1_u8).build(),
+        Outer {
+            first: Inner { value: 1_u32 },
+            second: 2,
+        }
+    );
+}
+
+#[test]
+fn test_into_builder() {
+    #[derive(Debug, Clone, PartialEq, TypedBuilder)]
+    #[builder(into_builder, field_defaults(setter(overridable)))]
+    struct Config {
+        host: String,
+        port: u16,
+        #[builder(default = 3)]
+        retries: u32,
+        #[builder(default = port + 1, setter(skip))]
+        admin_port: u16,
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(into_builder, field_defaults(setter(overridable)))]
+    struct Pair<T>(T, #[builder(setter(name = second))] T);
+
+    let config = Config::builder().host("localhost".to_owned()).port(80).build();
+    assert_eq!(
+        config.to_builder().port(8080).build(),
+        Config {
+            host: "localhost".to_owned(),
+            port: 8080,
+            retries: 3,
+            admin_port: 8081,
+        }
+    );
+    assert_eq!(config.clone().into_builder().build(), config);
+
+    // Overridable setters can be called more than once.
+    assert_eq!(Config::builder().host("a".to_owned()).port(1).port(2).build().port, 2);
+
+    assert_eq!(
+        Pair::builder()._0(1)._0(2).second(3).build().into_builder().second(4).build(),
+        Pair(2, 4)
+    );
+}
+
+#[test]
+fn test_setter_overridable() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(overridable))]
+        x: i32,
+        #[builder(default, setter(overridable, strip_option))]
+        y: Option<i32>,
+        z: i32,
+    }
+
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    #[builder(field_defaults(setter(overridable)))]
+    struct Bar {
+        x: i32,
+        #[builder(default)]
+        y: i32,
+    }
+
+    let preset = Foo::builder().x(1).y(2);
+    assert_eq!(preset.clone().z(3).build(), Foo { x: 1, y: Some(2), z: 3 });
+    assert_eq!(preset.x(4).z(3).y(5).build(), Foo { x: 4, y: Some(5), z: 3 });
+
+    assert_eq!(Bar::builder().x(1).y(2).x(3).y(4).build(), Bar { x: 3, y: 4 });
+}
+
+#[test]
+fn test_setter_reset() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        x: i32,
+        #[builder(default = 10, setter(reset))]
+        y: i32,
+        #[builder(default, setter(strip_option, reset = clear_z))]
+        z: Option<i32>,
+    }
+
+    let shared = Foo::builder().y(1).z(2);
+    assert_eq!(shared.clone().x(0).build(), Foo { x: 0, y: 1, z: Some(2) });
+    assert_eq!(shared.clone().reset_y().x(0).build(), Foo { x: 0, y: 10, z: Some(2) });
+    assert_eq!(shared.x(0).clear_z().reset_y().y(3).build(), Foo { x: 0, y: 3, z: None });
+    assert_eq!(Foo::builder().x(0).reset_y().build(), Foo { x: 0, y: 10, z: None });
+}
+
+#[test]
+fn test_strip_option_fallback() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        #[builder(setter(strip_option(fallback = x_opt)))]
+        x: Option<i32>,
+        #[builder(default = Some(5), setter(into, strip_option(fallback = y_opt)))]
+        y: Option<i64>,
+    }
+
+    assert_eq!(Foo::builder().x(1).build(), Foo { x: Some(1), y: Some(5) });
+    assert_eq!(Foo::builder().x_opt(None).y(2).build(), Foo { x: None, y: Some(2) });
+    assert_eq!(Foo::builder().y_opt(None).x_opt(Some(3)).build(), Foo { x: Some(3), y: None });
+}
+
+#[test]
+fn test_setter_maybe() {
+    #[derive(Debug, PartialEq, TypedBuilder)]
+    struct Foo {
+        x: i32,
+        #[builder(default = 10, setter(maybe))]
+        y: i32,
+        #[builder(default = Some(20), setter(strip_option, maybe = z_or_default))]
+        z: Option<i32>,
+    }
+
+    assert_eq!(Foo::builder().x(1).maybe_y(Some(2)).build(), Foo { x: 1, y: 2, z: Some(20) });
+    assert_eq!(
+        Foo::builder().maybe_y(None).x(1).build(),
+        Foo {
+            x: 1,
+            y: 10,
+            z: Some(20)
+        }
+    );
+    assert_eq!(
+        Foo::builder().x(1).z_or_default(Some(3)).build(),
+        Foo { x: 1, y: 10, z: Some(3) }
+    );
+    assert_eq!(
+        Foo::builder().z_or_default(None).x(1).build(),
+        Foo {
+            x: 1,
+            y: 10,
+            z: Some(20)
+        }
+    );
+
+    // Both branches produce the same builder type.
+    let from_config: Option<i32> = None;
+    let builder = Foo::builder().x(1).maybe_y(from_config);
+    assert_eq!(builder.build().y, 10);
+}
+
+#[test]
+fn test_dynamic() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(dynamic)]
+    struct Foo {
+        #[builder(setter(into))]
+        x: i32,
+        #[builder(setter(validate = |v: &u16| *v > 0))]
+        y: u16,
+        #[builder(default = 10, setter(maybe, reset))]
+        z: i32,
+        #[builder(default, setter(each = "item"))]
+        items: Vec<i32>,
+        #[builder(default, setter(strip_option))]
+        w: Option<i32>,
+    }
+
+    let mut builder = FooDynBuilder::new();
+    builder.x(1i8).item(1).item(2);
+    builder.w(3);
+    assert_eq!(builder.y(0).err(), Some(typed_builder::ValidationError { field: "y" }));
+    assert!(builder.y(2).is_ok());
+    assert_eq!(
+        builder.build(),
+        Ok(Foo {
+            x: 1,
+            y: 2,
+            z: 10,
+            items: vec![1, 2],
+            w: Some(3),
+        })
+    );
+
+    let mut builder = FooDynBuilder::default();
+    builder.z(5).reset_z().maybe_z(Some(6));
+    let error = builder.build().unwrap_err();
+    assert_eq!(error.missing_fields().collect::<Vec<_>>(), ["x", "y"]);
+    assert_eq!(error.to_string(), "missing required fields: `x`, `y`");
+
+    // The fields can be set in a loop, which the typed builder can't do.
+    let mut builder = FooDynBuilder::new();
+    for (name, value) in [("x", 1), ("y", 2), ("z", 3)] {
+        let value: u16 = value;
+        match name {
+            "x" => {
+                builder.x(value);
+            }
+            "y" => {
+                builder.y(value).unwrap();
+            }
+            _ => {
+                builder.z(value.into());
+            }
+        }
+    }
+    assert_eq!(builder.build().unwrap().z, 3);
+}
+
+#[test]
+fn test_dynamic_with_generics_and_build_conversion() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(dynamic, build_method(into))]
+    struct Foo<T> {
+        x: T,
+        #[builder(default = 2)]
+        y: i32,
+    }
+
+    impl<T> From<Foo<T>> for (T, i32) {
+        fn from(foo: Foo<T>) -> Self {
+            (foo.x, foo.y)
+        }
+    }
+
+    let mut builder = FooDynBuilder::<&str>::new();
+    builder.x("a");
+    let built: Result<(&str, i32), _> = builder.build();
+    assert_eq!(built, Ok(("a", 2)));
+}
+
+#[test]
+fn test_dynamic_conversions() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(dynamic)]
+    struct Foo {
+        x: i32,
+        y: i32,
+        #[builder(default = 10, setter(maybe))]
+        z: i32,
+    }
+
+    let mut builder = Foo::builder().x(1).maybe_z(None).into_dyn();
+    builder.y(2);
+    assert_eq!(builder.build(), Ok(Foo { x: 1, y: 2, z: 10 }));
+
+    let builder = Foo::builder().x(1).into_dyn();
+    // `y` is not set yet, so the builder is given back.
+    let Err(mut builder) = builder.try_into_typed::<((i32,), (i32,), ())>() else {
+        panic!("`y` should not be set");
+    };
+    builder.y(2);
+    let Ok(builder) = builder.try_into_typed::<((i32,), (i32,), ())>() else {
+        panic!("`x` and `y` should be set");
+    };
+    assert_eq!(builder.z(3).build(), Foo { x: 1, y: 2, z: 3 });
+
+    let mut builder = FooDynBuilder::new();
+    builder.x(1).y(2).z(3);
+    assert!(builder.try_into_typed::<((i32,), (i32,), ())>().is_err());
+    let mut builder = FooDynBuilder::new();
+    builder.x(1).y(2);
+    let Ok(builder) = builder.try_into_typed::<((i32,), (i32,), ())>() else {
+        panic!("`z` should not be set");
+    };
+    assert_eq!(builder.build(), Foo { x: 1, y: 2, z: 10 });
+}
+
+#[test]
+fn test_set_by_name() {
+    use typed_builder::SetByNameError;
+
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(dynamic)]
+    struct Foo {
+        #[builder(setter(from_str))]
+        x: i32,
+        #[builder(default, setter(from_str, strip_option))]
+        y: Option<u16>,
+        #[builder(default, setter(from_str, prefix = "with_", validate = |v: &u8| *v < 10))]
+        z: u8,
+        #[builder(default)]
+        w: i32,
+    }
+
+    let mut builder = FooDynBuilder::new();
+    builder.set_by_name("x", "1").unwrap().set_by_name("y", "2").unwrap();
+    assert_eq!(builder.set_by_name("z", "3").err(), Some(SetByNameError::UnknownField("z")));
+    assert_eq!(builder.set_by_name("w", "3").err(), Some(SetByNameError::UnknownField("w")));
+    assert_eq!(
+        builder.set_by_name("with_z", "30").err(),
+        Some(SetByNameError::InvalidValue { field: "z" })
+    );
+    assert_eq!(
+        builder.set_by_name("y", "-1").err(),
+        Some(SetByNameError::InvalidValue { field: "y" })
+    );
+    builder.set_by_name("with_z", "3").unwrap();
+    assert_eq!(
+        builder.build(),
+        Ok(Foo {
+            x: 1,
+            y: Some(2),
+            z: 3,
+            w: 0
+        })
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(deserialize)]
+    struct Foo {
+        name: String,
+        #[builder(default = "localhost".to_owned())]
+        host: String,
+        #[builder(default = 80, setter(overridable))]
+        port: u16,
+        #[builder(default)]
+        tags: Vec<String>,
+    }
+
+    let partial: FooPartial = serde_json::from_str(r#"{"host": "example.com", "port": 8080}"#).unwrap();
+    assert_eq!(
+        partial.name("foo".to_owned()).port(9090).build(),
+        Foo {
+            name: "foo".to_owned(),
+            host: "example.com".to_owned(),
+            port: 9090,
+            tags: vec![],
+        }
+    );
+
+    let partial: FooPartial = serde_json::from_str("{}").unwrap();
+    assert_eq!(
+        partial.name("foo".to_owned()).build(),
+        Foo {
+            name: "foo".to_owned(),
+            host: "localhost".to_owned(),
+            port: 80,
+            tags: vec![],
+        }
+    );
+
+    assert!(serde_json::from_str::<FooPartial>(r#"{"port": "http"}"#).is_err());
+    assert!(serde_json::from_str::<FooPartial>(r#"{"port": 1, "port": 2}"#).is_err());
+
+    // Fields without a default are read from the data when the partial's type says they are set.
+    let partial: FooPartial<(String,)> = serde_json::from_str(r#"{"name": "foo", "port": 8080}"#).unwrap();
+    assert_eq!(
+        partial.build(),
+        Foo {
+            name: "foo".to_owned(),
+            host: "localhost".to_owned(),
+            port: 8080,
+            tags: vec![],
+        }
+    );
+
+    let Err(error) = serde_json::from_str::<FooPartial<(String,)>>(r#"{"port": 1}"#) else {
+        panic!("`name` should be required in the data");
+    };
+    assert!(error.to_string().starts_with("missing field `name`"));
+
+    let Err(error) = serde_json::from_str::<FooPartial>(r#"{"port": 1, "name": "foo"}"#) else {
+        panic!("`name` should be left for its setter");
+    };
+    assert!(error
+        .to_string()
+        .starts_with("field `name` has no default, and the partial's type leaves it for its setter"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_rename_and_generics() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(deserialize)]
+    struct Foo<'a, T> {
+        x: &'a str,
+        #[builder(default)]
+        #[serde(rename = "why")]
+        y: Option<T>,
+        #[builder(default)]
+        #[serde(alias = "zed")]
+        z: u8,
+    }
+
+    let partial: FooPartial<'_, i32> = serde_json::from_str(r#"{"why": 1, "y": 2, "zed": 3}"#).unwrap();
+    assert_eq!(
+        partial.x("x").build(),
+        Foo {
+            x: "x",
+            y: Some(1),
+            z: 3
+        }
+    );
+
+    let partial: FooPartial<'_, i32, (&str,)> = serde_json::from_str(r#"{"x": "from data", "why": 1}"#).unwrap();
+    assert_eq!(
+        partial.build(),
+        Foo {
+            x: "from data",
+            y: Some(1),
+            z: 0
+        }
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_with_env() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Foo {
+        name: String,
+        #[builder(default = 80, env = "TYPED_BUILDER_TEST_PORT")]
+        port: u16,
+        #[builder(default, env = "TYPED_BUILDER_TEST_HOST", setter(strip_option))]
+        host: Option<String>,
+        #[builder(default = 1, env = "TYPED_BUILDER_TEST_WORKERS", setter(validate = |v: &u8| *v > 0))]
+        workers: u8,
+    }
+
+    std::env::set_var("TYPED_BUILDER_TEST_PORT", "8080");
+    std::env::remove_var("TYPED_BUILDER_TEST_HOST");
+    std::env::remove_var("TYPED_BUILDER_TEST_WORKERS");
+    assert_eq!(
+        Foo::builder().name("foo".to_owned()).with_env().unwrap().build(),
+        Foo {
+            name: "foo".to_owned(),
+            port: 8080,
+            host: None,
+            workers: 1,
+        }
+    );
+
+    std::env::set_var("TYPED_BUILDER_TEST_HOST", "localhost");
+    assert_eq!(
+        Foo::builder()
+            .with_env()
+            .unwrap()
+            .name("foo".to_owned())
+            .build()
+            .host
+            .as_deref(),
+        Some("localhost")
+    );
+
+    std::env::set_var("TYPED_BUILDER_TEST_PORT", "http");
+    std::env::set_var("TYPED_BUILDER_TEST_WORKERS", "0");
+    let Err(error) = Foo::builder().with_env() else {
+        panic!("the variables should fail to parse");
+    };
+    assert_eq!(error.variables(), ["TYPED_BUILDER_TEST_PORT", "TYPED_BUILDER_TEST_WORKERS"]);
+    assert_eq!(
+        error.to_string(),
+        "invalid environment variables: `TYPED_BUILDER_TEST_PORT`, `TYPED_BUILDER_TEST_WORKERS`"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_errors_implement_std_error() {
+    fn assert_error<E: std::error::Error>() {}
+
+    assert_error::<typed_builder::ValidationError>();
+    assert_error::<typed_builder::MissingFieldsError>();
+    assert_error::<typed_builder::SetByNameError<'static>>();
+}
+
+#[test]
+fn test_exactly_one_group() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Foo {
+        name: &'static str,
+        #[builder(default, setter(strip_option), group(source, exactly_one))]
+        path: Option<&'static str>,
+        #[builder(default, setter(strip_option), group(source))]
+        url: Option<&'static str>,
+        #[builder(default, setter(strip_option(fallback = inline_data_opt)), group(source))]
+        inline_data: Option<Vec<u8>>,
+    }
+
+    assert_eq!(
+        Foo::builder().path("a.txt").name("foo").build(),
+        Foo {
+            name: "foo",
+            path: Some("a.txt"),
+            url: None,
+            inline_data: None,
+        }
+    );
+    assert_eq!(
+        Foo::builder().name("foo").url("http://example.com").build().url,
+        Some("http://example.com")
+    );
+    assert_eq!(
+        Foo::builder()
+            .inline_data_opt(Some(vec![1, 2]))
+            .name("foo")
+            .build()
+            .inline_data,
+        Some(vec![1, 2])
+    );
+}
+
+#[test]
+fn test_at_least_one_group() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Request {
+        #[builder(default, setter(strip_option), group(filter, at_least_one))]
+        name: Option<&'static str>,
+        #[builder(default, setter(strip_option), group(filter))]
+        id: Option<u32>,
+        #[builder(default, setter(strip_option), group(filter))]
+        tag: Option<&'static str>,
+        #[builder(default = 10)]
+        limit: usize,
+    }
+
+    assert_eq!(
+        Request::builder().tag("x").build(),
+        Request {
+            name: None,
+            id: None,
+            tag: Some("x"),
+            limit: 10,
+        }
+    );
+    assert_eq!(
+        Request::builder().id(1).limit(5).name("foo").build(),
+        Request {
+            name: Some("foo"),
+            id: Some(1),
+            tag: None,
+            limit: 5,
+        }
+    );
+}
+
+#[test]
+fn test_requires_and_conflicts_with() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Login {
+        #[builder(default, setter(strip_option), requires(password))]
+        username: Option<&'static str>,
+        #[builder(default, setter(strip_option))]
+        password: Option<&'static str>,
+        #[builder(setter(strip_bool), conflicts_with(username))]
+        anonymous: bool,
+    }
+
+    assert_eq!(
+        Login::builder().username("user").password("hunter2").build(),
+        Login {
+            username: Some("user"),
+            password: Some("hunter2"),
+            anonymous: false,
+        }
+    );
+    assert_eq!(
+        Login::builder().anonymous().build(),
+        Login {
+            username: None,
+            password: None,
+            anonymous: true,
+        }
+    );
+    // `password` can be set without `username`.
+    assert_eq!(Login::builder().password("hunter2").build().password, Some("hunter2"));
+}
+
+#[test]
+fn test_default_refers_to_later_field() {
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Foo {
+        #[builder(default = format!("{}:{}", host, port))]
+        address: String,
+        #[builder(default = "localhost".to_owned())]
+        host: String,
+        #[builder(default = if secure { 443 } else { 80 })]
+        port: u16,
+        secure: bool,
+    }
+
+    assert_eq!(
+        Foo::builder().secure(true).build(),
+        Foo {
+            address: "localhost:443".to_owned(),
+            host: "localhost".to_owned(),
+            port: 443,
+            secure: true,
+        }
+    );
+    assert_eq!(
+        Foo::builder().host("example.com".to_owned()).secure(false).build().address,
+        "example.com:80"
+    );
+}
+
+#[test]
+fn test_default_bindings_are_not_dependencies() {
+    #[derive(PartialEq, Debug)]
+    struct A {
+        y: i32,
+    }
+
+    #[derive(PartialEq, Debug)]
+    struct B {
+        x: i32,
+    }
+
+    // Struct literal field names, closure parameters and `let` bindings that share their name with
+    // a field don't make the default depend on it.
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Foo {
+        #[builder(default = A { y: z })]
+        x: A,
+        #[builder(default = B { x: w })]
+        y: B,
+        #[builder(default = { let x = 3; x * 2 })]
+        z: i32,
+        #[builder(default = [1, 2].iter().map(|y| y * 10).sum())]
+        w: i32,
+    }
+
+    assert_eq!(
+        Foo::builder().build(),
+        Foo {
+            x: A { y: 6 },
+            y: B { x: 30 },
+            z: 6,
+            w: 30,
+        }
+    );
+    assert_eq!(Foo::builder().z(1).build().x, A { y: 1 });
+}
+
+#[test]
+fn test_default_pattern_bindings_are_not_dependencies() {
+    fn lookup(key: &str) -> Option<String> {
+        (!key.is_empty()).then(|| key.to_owned())
+    }
+
+    // Variables bound by `if let`, `match` arms, `for` and `while let` that share their name with a
+    // field don't make the default depend on it.
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Foo {
+        #[builder(default = if let Some(x) = lookup("found") { x } else { "missing".to_owned() })]
+        y: String,
+        #[builder(default = y.clone())]
+        x: String,
+        #[builder(default = match lookup("found") { Some(b) => b.len(), None => 0 })]
+        a: usize,
+        #[builder(default = a + 1)]
+        b: usize,
+        #[builder(default = { let mut total = 0; for c in [1, 2] { total += c; } total })]
+        d: i32,
+        #[builder(default = d * 2)]
+        c: i32,
+        #[builder(default = { let mut items = vec![1, 2, 3]; let mut sum = 0; while let Some(e) = items.pop() { sum += e; } sum })]
+        f: i32,
+        #[builder(default = f + 1)]
+        e: i32,
+    }
+
+    assert_eq!(
+        Foo::builder().build(),
+        Foo {
+            y: "found".to_owned(),
+            x: "found".to_owned(),
+            a: 5,
+            b: 6,
+            d: 3,
+            c: 6,
+            f: 6,
+            e: 7,
+        }
+    );
+}
+
+#[test]
+fn test_default_dependencies_in_closures_and_format_strings() {
+    // `port` is only bound inside the closure, so the call after it refers to the field, and so do
+    // the variables captured by the format string.
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    struct Foo {
+        #[builder(default = { let double = |port: u16| port * 2; double(port) })]
+        doubled: u16,
+        #[builder(default = format!("{host}:{port}"))]
+        address: String,
+        #[builder(default = "localhost")]
+        host: &'static str,
+        #[builder(default = 80)]
+        port: u16,
+    }
+
+    assert_eq!(
+        Foo::builder().build(),
+        Foo {
+            doubled: 160,
+            address: "localhost:80".to_owned(),
+            host: "localhost",
+            port: 80,
+        }
+    );
+    assert_eq!(Foo::builder().port(8080).build().address, "localhost:8080");
+    assert_eq!(Foo::builder().port(8080).build().doubled, 16160);
+}
+
+#[test]
+fn test_default_from_ctx() {
+    struct AppCtx {
+        timeout: u32,
+        region: &'static str,
+    }
+
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(build_method(context = AppCtx))]
+    struct Request {
+        url: &'static str,
+        #[builder(default_from_ctx = |ctx: &AppCtx| ctx.timeout)]
+        timeout: u32,
+        #[builder(default_from_ctx = |ctx: &AppCtx| ctx.region.to_owned())]
+        region: String,
+        #[builder(default = timeout * 2)]
+        deadline: u32,
+    }
+
+    let ctx = AppCtx {
+        timeout: 30,
+        region: "eu",
+    };
+    assert_eq!(
+        Request::builder().url("/").build_with(&ctx),
+        Request {
+            url: "/",
+            timeout: 30,
+            region: "eu".to_owned(),
+            deadline: 60,
+        }
+    );
+    assert_eq!(
+        Request::builder().timeout(5).url("/").build_with(&ctx),
+        Request {
+            url: "/",
+            timeout: 5,
+            region: "eu".to_owned(),
+            deadline: 10,
+        }
+    );
+    assert_eq!(
+        Request::builder().url("/").timeout(1).region("us".to_owned()).build(),
+        Request {
+            url: "/",
+            timeout: 1,
+            region: "us".to_owned(),
+            deadline: 2,
+        }
+    );
+}
+
+#[test]
+fn test_default_refers_to_default_from_ctx_field() {
+    struct AppCtx {
+        timeout: u32,
+    }
+
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(build_method(context = AppCtx))]
+    struct Request {
+        #[builder(default = timeout * retries)]
+        budget: u32,
+        #[builder(default_from_ctx = |ctx: &AppCtx| ctx.timeout + retries)]
+        timeout: u32,
+        #[builder(default = 3)]
+        retries: u32,
+    }
+
+    let ctx = AppCtx { timeout: 10 };
+    assert_eq!(
+        Request::builder().build_with(&ctx),
+        Request {
+            budget: 39,
+            timeout: 13,
+            retries: 3,
+        }
+    );
+    assert_eq!(Request::builder().retries(1).build_with(&ctx).budget, 11);
+    assert_eq!(Request::builder().timeout(2).build().budget, 6);
+}
+
+#[test]
+fn test_async_build() {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        fn raw_waker() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker {
+                raw_waker()
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(core::ptr::null(), &VTABLE)
+        }
+
+        let waker = unsafe { Waker::from_raw(raw_waker()) };
+        let mut context = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    fn resolve(host: &str) -> core::future::Ready<String> {
+        core::future::ready(format!("{host}:80"))
+    }
+
+    fn check(request: &Request) -> core::future::Ready<Result<(), String>> {
+        core::future::ready(if request.address.is_empty() {
+            Err("empty address".to_owned())
+        } else {
+            Ok(())
+        })
+    }
+
+    #[derive(PartialEq, Debug, TypedBuilder)]
+    #[builder(build_method(async, validate_async = check, error = String))]
+    struct Request {
+        #[builder(default = address.len())]
+        address_len: usize,
+        host: &'static str,
+        #[builder(default_async = resolve(host))]
+        address: String,
+    }
+
+    assert_eq!(
+        block_on(Request::builder().host("localhost").build()),
+        Ok(Request {
+            address_len: 12,
+            host: "localhost",
+            address: "localhost:80".to_owned(),
+        })
+    );
+    assert_eq!(
+        block_on(Request::builder().host("localhost").address(String::new()).build()),
+        Err("empty address".to_owned())
+    );
+}
@@ -1,34 +1,92 @@
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::{parse::Error, spanned::Spanned};
 
 use crate::util::{
-    apply_subsections, expr_to_lit_string, expr_to_single_string, ident_to_type, path_to_single_string, strip_raw_ident_prefix,
+    apply_subsections, empty_type, expr_to_lit_string, expr_to_single_string, ident_to_type, path_to_single_string,
+    strip_raw_ident_prefix,
 };
 
 #[derive(Debug)]
 pub struct FieldInfo<'a> {
     pub ordinal: usize,
-    pub name: &'a syn::Ident,
+    /// The name of the field - or, for fields of tuple structs, the name given to it with
+    /// `setter(name = ...)` or `_0`, `_1`... by default.
+    pub name: syn::Ident,
     pub generic_ident: syn::Ident,
     pub ty: &'a syn::Type,
     pub builder_attr: FieldBuilderAttr<'a>,
+    /// The name set with `#[serde(rename = "...")]`, used for the field when deserializing a
+    /// builder.
+    pub serde_rename: Option<String>,
+    /// The other names set with `#[serde(alias = "...")]`, also accepted when deserializing a
+    /// builder.
+    pub serde_aliases: Vec<String>,
 }
 
 impl<'a> FieldInfo<'a> {
     pub fn new(ordinal: usize, field: &'a syn::Field, field_defaults: FieldBuilderAttr<'a>) -> Result<FieldInfo<'a>, Error> {
-        if let Some(ref name) = field.ident {
-            FieldInfo {
-                ordinal,
-                name,
-                generic_ident: syn::Ident::new(&format!("__{}", strip_raw_ident_prefix(name.to_string())), Span::call_site()),
-                ty: &field.ty,
-                builder_attr: field_defaults.with(&field.attrs)?,
-            }
-            .post_process()
+        let builder_attr = field_defaults.with(&field.attrs)?;
+        let name = if let Some(ref name) = field.ident {
+            name.clone()
+        } else if let Some(ref name) = builder_attr.setter.name {
+            name.clone()
         } else {
-            Err(Error::new(field.span(), "Nameless field in struct"))
+            syn::Ident::new(&format!("_{}", ordinal), Span::call_site())
+        };
+        FieldInfo {
+            ordinal,
+            generic_ident: syn::Ident::new(&format!("__{}", strip_raw_ident_prefix(name.to_string())), Span::call_site()),
+            name,
+            ty: &field.ty,
+            builder_attr,
+            serde_rename: None,
+            serde_aliases: Vec::new(),
+        }
+        .post_process()
+    }
+
+    /// Read the names given to the field by `#[serde(rename = "...")]`,
+    /// `#[serde(rename(deserialize = "..."))]` and `#[serde(alias = "...")]`. Other serde options
+    /// are left for serde to check.
+    pub fn parse_serde_attrs(&mut self, attrs: &[syn::Attribute]) -> Result<(), Error> {
+        for attr in attrs {
+            if !attr.path().is_ident("serde") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if meta.input.peek(syn::Token![=]) {
+                        self.serde_rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                    } else {
+                        meta.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("deserialize") {
+                                self.serde_rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                            } else {
+                                meta.value()?.parse::<syn::LitStr>()?;
+                            }
+                            Ok(())
+                        })?;
+                    }
+                } else if meta.path.is_ident("alias") {
+                    self.serde_aliases.push(meta.value()?.parse::<syn::LitStr>()?.value());
+                } else if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            })?;
         }
+        Ok(())
+    }
+
+    /// The name of the field in deserialized data.
+    pub fn serde_name(&self) -> String {
+        self.serde_rename
+            .clone()
+            .unwrap_or_else(|| strip_raw_ident_prefix(self.name.to_string()))
     }
 
     pub fn generic_ty_param(&self) -> syn::GenericParam {
@@ -39,6 +97,23 @@ impl<'a> FieldInfo<'a> {
         ident_to_type(self.generic_ident.clone())
     }
 
+    /// The type of the field in the builder before its setter is called - `()`, or the builder of
+    /// the field's type for `flatten` fields.
+    pub fn initial_state_type(&self, crate_module_path: &syn::Path) -> syn::Type {
+        if self.builder_attr.flatten.is_some() {
+            let ty = self.ty;
+            syn::parse_quote!(<#ty as #crate_module_path::HasTypedBuilder>::Builder)
+        } else {
+            empty_type()
+        }
+    }
+
+    /// The type of the field in the builder after its `maybe_` setter is called.
+    pub fn maybe_state_type(&self, crate_module_path: &syn::Path) -> syn::Type {
+        let ty = self.ty;
+        syn::parse_quote!(#crate_module_path::Maybe<#ty>)
+    }
+
     pub fn tuplized_type_ty_param(&self) -> syn::Type {
         let mut types = syn::punctuated::Punctuated::default();
         types.push(self.ty.clone());
@@ -75,8 +150,30 @@ impl<'a> FieldInfo<'a> {
         }
     }
 
+    /// The generic arguments of the field's type - e.g. `[K, V]` for `BTreeMap<K, V>`.
+    pub fn type_generic_args(&self) -> Vec<&syn::Type> {
+        let syn::Type::Path(type_path) = self.ty else {
+            return Vec::new();
+        };
+        let Some(segment) = type_path.path.segments.last() else {
+            return Vec::new();
+        };
+        let syn::PathArguments::AngleBracketed(generic_params) = &segment.arguments else {
+            return Vec::new();
+        };
+        generic_params
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn setter_method_name(&self) -> Ident {
-        let name = strip_raw_ident_prefix(self.name.to_string());
+        let base_name = self.builder_attr.setter.name.as_ref().unwrap_or(&self.name);
+        let name = strip_raw_ident_prefix(base_name.to_string());
 
         if let (Some(prefix), Some(suffix)) = (&self.builder_attr.setter.prefix, &self.builder_attr.setter.suffix) {
             Ident::new(&format!("{}{}{}", prefix, name, suffix), Span::call_site())
@@ -85,11 +182,60 @@ impl<'a> FieldInfo<'a> {
         } else if let Some(suffix) = &self.builder_attr.setter.suffix {
             Ident::new(&format!("{}{}", name, suffix), Span::call_site())
         } else {
-            self.name.clone()
+            base_name.clone()
         }
     }
 
+    /// The name of the method that returns the field to its unset state.
+    pub fn reset_method_name(&self) -> Ident {
+        if let Some(name) = &self.builder_attr.setter.reset_name {
+            return name.clone();
+        }
+        let base_name = self.builder_attr.setter.name.as_ref().unwrap_or(&self.name);
+        Ident::new(
+            &format!("reset_{}", strip_raw_ident_prefix(base_name.to_string())),
+            Span::call_site(),
+        )
+    }
+
+    /// The name of the setter that accepts an `Option` and uses the default for `None`.
+    pub fn maybe_method_name(&self) -> Ident {
+        if let Some(name) = &self.builder_attr.setter.maybe_name {
+            return name.clone();
+        }
+        let base_name = self.builder_attr.setter.name.as_ref().unwrap_or(&self.name);
+        Ident::new(
+            &format!("maybe_{}", strip_raw_ident_prefix(base_name.to_string())),
+            Span::call_site(),
+        )
+    }
+
     fn post_process(mut self) -> Result<Self, Error> {
+        if let Some(default_from_ctx) = &self.builder_attr.default_from_ctx {
+            check_conflicts(
+                "default_from_ctx",
+                default_from_ctx.span(),
+                &[
+                    ("default", self.builder_attr.default.as_ref().map(Spanned::span)),
+                    ("skip", self.builder_attr.setter.skip),
+                    ("flatten", self.builder_attr.flatten),
+                    ("strip_bool", self.builder_attr.setter.strip_bool),
+                    ("each", self.builder_attr.setter.each.as_ref().map(|each| each.name.span())),
+                ],
+            )?;
+        }
+        if let Some(each) = &self.builder_attr.setter.each {
+            if let (Some(key_value), true) = (each.key_value, self.type_generic_args().len() < 2) {
+                return Err(Error::new(
+                    key_value,
+                    "key_value requires a field type with key and value type arguments, like `HashMap<K, V>`",
+                ));
+            }
+            if self.builder_attr.default.is_none() {
+                self.builder_attr.default =
+                    Some(syn::parse2(quote_spanned!(each.span=> ::core::default::Default::default())).unwrap());
+            }
+        }
         if let Some(ref strip_bool_span) = self.builder_attr.setter.strip_bool {
             if let Some(default_span) = self.builder_attr.default.as_ref().map(Spanned::span) {
                 let mut error = Error::new(
@@ -107,6 +253,39 @@ impl<'a> FieldInfo<'a> {
                 }),
             }));
         }
+        if let (Some(reset), None) = (self.builder_attr.setter.reset, &self.builder_attr.default) {
+            return Err(Error::new(reset, "reset requires the field to have a default"));
+        }
+        if let (Some(maybe), None) = (self.builder_attr.setter.maybe, &self.builder_attr.default) {
+            return Err(Error::new(maybe, "maybe requires the field to have a default"));
+        }
+        if let (Some(env), None) = (&self.builder_attr.env, &self.builder_attr.default) {
+            return Err(Error::new_spanned(env, "env requires the field to have a default"));
+        }
+        if let Some(group) = self.builder_attr.groups.first() {
+            if self.builder_attr.default.is_none() {
+                return Err(Error::new_spanned(&group.name, "group members must have a default"));
+            }
+        }
+        let constraint = (self.builder_attr.groups.first().map(|group| ("group", &group.name)))
+            .or_else(|| self.builder_attr.requires.first().map(|name| ("requires", name)))
+            .or_else(|| self.builder_attr.conflicts_with.first().map(|name| ("conflicts_with", name)));
+        if let Some((constraint_caption, constraint_name)) = constraint {
+            check_conflicts(
+                constraint_caption,
+                constraint_name.span(),
+                &[
+                    ("skip", self.builder_attr.setter.skip),
+                    ("maybe", self.builder_attr.setter.maybe),
+                    ("env", self.builder_attr.env.as_ref().map(Spanned::span)),
+                    ("flatten", self.builder_attr.flatten),
+                    (
+                        "default_from_ctx",
+                        self.builder_attr.default_from_ctx.as_ref().map(Spanned::span),
+                    ),
+                ],
+            )?;
+        }
         Ok(self)
     }
 }
@@ -114,6 +293,21 @@ impl<'a> FieldInfo<'a> {
 #[derive(Debug, Default, Clone)]
 pub struct FieldBuilderAttr<'a> {
     pub default: Option<syn::Expr>,
+    /// Whether `default` is a future that the (async) build method awaits, set with
+    /// `default_async = ...`.
+    pub default_async: Option<Span>,
+    /// A function that computes the field's default from the context passed to the `build_with`
+    /// method, set with `default_from_ctx = ...`.
+    pub default_from_ctx: Option<syn::Expr>,
+    pub flatten: Option<Span>,
+    /// The environment variable the field is read from by the builder's `with_env` method.
+    pub env: Option<syn::LitStr>,
+    /// The groups the field is a member of, set with `group(name, kind)`.
+    pub groups: Vec<GroupMembership>,
+    /// The fields that must also be set when this field is set, set with `requires(...)`.
+    pub requires: Vec<syn::Ident>,
+    /// The fields that can't be set together with this field, set with `conflicts_with(...)`.
+    pub conflicts_with: Vec<syn::Ident>,
     pub deprecated: Option<&'a syn::Attribute>,
     pub setter: SetterSettings,
 }
@@ -121,11 +315,27 @@ pub struct FieldBuilderAttr<'a> {
 #[derive(Debug, Default, Clone)]
 pub struct SetterSettings {
     pub doc: Option<syn::Expr>,
+    pub name: Option<syn::Ident>,
     pub skip: Option<Span>,
     pub auto_into: Option<Span>,
     pub strip_option: Option<Span>,
+    /// The name of the setter that accepts the whole `Option`, set with
+    /// `strip_option(fallback = ...)`.
+    pub strip_option_fallback: Option<syn::Ident>,
     pub strip_bool: Option<Span>,
+    pub nested: Option<Span>,
+    pub overridable: Option<Span>,
+    pub reset: Option<Span>,
+    pub reset_name: Option<syn::Ident>,
+    pub maybe: Option<Span>,
+    pub maybe_name: Option<syn::Ident>,
+    /// Whether the field can be set from a string with the `set_by_name` method of the
+    /// runtime-checked builder.
+    pub from_str: Option<Span>,
     pub transform: Option<Transform>,
+    pub try_transform: Option<Transform>,
+    pub validate: Option<syn::Expr>,
+    pub each: Option<EachSettings>,
     pub prefix: Option<String>,
     pub suffix: Option<String>,
 }
@@ -174,9 +384,31 @@ impl<'a> FieldBuilderAttr<'a> {
                     expr_to_single_string(&assign.left).ok_or_else(|| Error::new_spanned(&assign.left, "Expected identifier"))?;
                 match name.as_str() {
                     "default" => {
+                        self.default = Some(*assign.right);
+                        self.default_async = None;
+                        Ok(())
+                    }
+                    "default_async" => {
+                        self.default_async = Some(assign.left.span());
                         self.default = Some(*assign.right);
                         Ok(())
                     }
+                    "env" => {
+                        if let syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(name),
+                            ..
+                        }) = *assign.right
+                        {
+                            self.env = Some(name);
+                            Ok(())
+                        } else {
+                            Err(Error::new_spanned(assign.right, "Expected string"))
+                        }
+                    }
+                    "default_from_ctx" => {
+                        self.default_from_ctx = Some(*assign.right);
+                        Ok(())
+                    }
                     "default_code" => {
                         if let syn::Expr::Lit(syn::ExprLit {
                             lit: syn::Lit::Str(code),
@@ -187,6 +419,7 @@ impl<'a> FieldBuilderAttr<'a> {
                             let tokenized_code = TokenStream::from_str(&code.value())?;
                             self.default =
                                 Some(syn::parse2(tokenized_code).map_err(|e| Error::new_spanned(code, format!("{}", e)))?);
+                            self.default_async = None;
                         } else {
                             return Err(Error::new_spanned(assign.right, "Expected string"));
                         }
@@ -200,6 +433,11 @@ impl<'a> FieldBuilderAttr<'a> {
                 match name.as_str() {
                     "default" => {
                         self.default = Some(syn::parse2(quote!(::core::default::Default::default())).unwrap());
+                        self.default_async = None;
+                        Ok(())
+                    }
+                    "flatten" => {
+                        self.flatten = Some(path.span());
                         Ok(())
                     }
                     _ => Err(Error::new_spanned(&path, format!("Unknown parameter {:?}", name))),
@@ -223,6 +461,51 @@ impl<'a> FieldBuilderAttr<'a> {
                         }
                         Ok(())
                     }
+                    "group" => {
+                        let mut args = call.args.into_iter();
+                        let name = args
+                            .next()
+                            .and_then(|arg| match arg {
+                                syn::Expr::Path(path) => path.path.get_ident().cloned(),
+                                _ => None,
+                            })
+                            .ok_or_else(|| Error::new_spanned(&call.func, "group(...) requires the group's name"))?;
+                        let mut kind = None;
+                        for arg in args {
+                            kind = Some(match expr_to_single_string(&arg).as_deref() {
+                                Some("exactly_one") => GroupKind::ExactlyOne,
+                                Some("at_least_one") => GroupKind::AtLeastOne,
+                                _ => return Err(Error::new_spanned(arg, "Expected `exactly_one` or `at_least_one`")),
+                            });
+                        }
+                        self.groups.push(GroupMembership { name, kind });
+                        Ok(())
+                    }
+                    "requires" | "conflicts_with" => {
+                        let names = call
+                            .args
+                            .into_iter()
+                            .map(|arg| {
+                                match &arg {
+                                    syn::Expr::Path(path) => path.path.get_ident().cloned(),
+                                    _ => None,
+                                }
+                                .ok_or_else(|| Error::new_spanned(arg, "Expected field name"))
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+                        if names.is_empty() {
+                            return Err(Error::new_spanned(
+                                &call.func,
+                                format_args!("{}(...) requires at least one field name", subsetting_name),
+                            ));
+                        }
+                        if subsetting_name == "requires" {
+                            self.requires.extend(names);
+                        } else {
+                            self.conflicts_with.extend(names);
+                        }
+                        Ok(())
+                    }
                     _ => Err(Error::new_spanned(
                         &call.func,
                         format!("Illegal builder setting group name {}", subsetting_name),
@@ -240,6 +523,19 @@ impl<'a> FieldBuilderAttr<'a> {
                     match name.as_str() {
                         "default" => {
                             self.default = None;
+                            self.default_async = None;
+                            Ok(())
+                        }
+                        "default_from_ctx" => {
+                            self.default_from_ctx = None;
+                            Ok(())
+                        }
+                        "flatten" => {
+                            self.flatten = None;
+                            Ok(())
+                        }
+                        "env" => {
+                            self.env = None;
                             Ok(())
                         }
                         _ => Err(Error::new_spanned(path, "Unknown setting".to_owned())),
@@ -260,16 +556,105 @@ impl<'a> FieldBuilderAttr<'a> {
             ));
         }
 
+        if let Some(flatten) = self.flatten {
+            check_conflicts(
+                "flatten",
+                flatten,
+                &[
+                    ("default", self.default.as_ref().map(Spanned::span)),
+                    ("skip", self.setter.skip),
+                ],
+            )?;
+        }
+
+        if let Some(env) = &self.env {
+            check_conflicts(
+                "env",
+                env.span(),
+                &[
+                    ("skip", self.setter.skip),
+                    ("flatten", self.flatten),
+                    ("nested", self.setter.nested),
+                    ("transform", self.setter.transform.as_ref().map(|t| t.span)),
+                    ("try_transform", self.setter.try_transform.as_ref().map(|t| t.span)),
+                    ("each", self.setter.each.as_ref().map(|e| e.span)),
+                ],
+            )?;
+        }
+
         let conflicting_transformations = [
             ("transform", self.setter.transform.as_ref().map(|t| &t.span)),
+            ("try_transform", self.setter.try_transform.as_ref().map(|t| &t.span)),
             ("strip_option", self.setter.strip_option.as_ref()),
             ("strip_bool", self.setter.strip_bool.as_ref()),
+            ("nested", self.setter.nested.as_ref()),
         ];
         let mut conflicting_transformations = conflicting_transformations
             .iter()
             .filter_map(|(caption, span)| span.map(|span| (caption, span)))
             .collect::<Vec<_>>();
 
+        if let Some(validate) = &self.setter.validate {
+            if let Some(fallback) = &self.setter.strip_option_fallback {
+                let mut error = Error::new_spanned(validate, "validate conflicts with strip_option(fallback = ...)");
+                error.combine(Error::new_spanned(fallback, "fallback set here"));
+                return Err(error);
+            }
+            check_conflicts(
+                "validate",
+                validate.span(),
+                &[
+                    ("skip", self.setter.skip),
+                    ("strip_bool", self.setter.strip_bool),
+                    ("try_transform", self.setter.try_transform.as_ref().map(|t| t.span)),
+                ],
+            )?;
+        }
+
+        if let Some(each) = &self.setter.each {
+            check_conflicts(
+                "each",
+                each.span,
+                &[
+                    ("skip", self.setter.skip),
+                    ("strip_option", self.setter.strip_option),
+                    ("strip_bool", self.setter.strip_bool),
+                    ("default_async", self.default_async),
+                    ("transform", self.setter.transform.as_ref().map(|t| t.span)),
+                    ("try_transform", self.setter.try_transform.as_ref().map(|t| t.span)),
+                    ("validate", self.setter.validate.as_ref().map(Spanned::span)),
+                ],
+            )?;
+        }
+
+        if let Some(maybe) = self.setter.maybe {
+            check_conflicts(
+                "maybe",
+                maybe,
+                &[
+                    ("strip_bool", self.setter.strip_bool),
+                    ("nested", self.setter.nested),
+                    ("transform", self.setter.transform.as_ref().map(|t| t.span)),
+                    ("try_transform", self.setter.try_transform.as_ref().map(|t| t.span)),
+                    ("validate", self.setter.validate.as_ref().map(Spanned::span)),
+                ],
+            )?;
+        }
+
+        if let Some(from_str) = self.setter.from_str {
+            check_conflicts(
+                "from_str",
+                from_str,
+                &[
+                    ("skip", self.setter.skip),
+                    ("nested", self.setter.nested),
+                    ("transform", self.setter.transform.as_ref().map(|t| t.span)),
+                    ("try_transform", self.setter.try_transform.as_ref().map(|t| t.span)),
+                    ("each", self.setter.each.as_ref().map(|e| e.span)),
+                ],
+            )?;
+        }
+
         if 1 < conflicting_transformations.len() {
             let (first_caption, first_span) = conflicting_transformations.pop().unwrap();
             let conflicting_captions = conflicting_transformations
@@ -289,6 +674,18 @@ impl<'a> FieldBuilderAttr<'a> {
     }
 }
 
+/// Fails with a "`setting` conflicts with ..." error pointing at `span` if any of the `conflicts` is set.
+fn check_conflicts(setting: &str, span: Span, conflicts: &[(&str, Option<Span>)]) -> Result<(), Error> {
+    for (caption, conflict_span) in conflicts {
+        if let Some(conflict_span) = conflict_span {
+            let mut error = Error::new(span, format_args!("{} conflicts with {}", setting, caption));
+            error.combine(Error::new(*conflict_span, format_args!("{} set here", caption)));
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
 impl SetterSettings {
     fn apply_meta(&mut self, expr: syn::Expr) -> Result<(), Error> {
         match expr {
@@ -300,10 +697,51 @@ impl SetterSettings {
                         self.doc = Some(*assign.right);
                         Ok(())
                     }
+                    "name" => {
+                        let name = expr_to_single_string(&assign.right)
+                            .ok_or_else(|| Error::new_spanned(&assign.right, "Expected identifier"))?;
+                        self.name = Some(syn::parse_str(&name).map_err(|e| Error::new_spanned(&assign.right, e))?);
+                        Ok(())
+                    }
                     "transform" => {
                         self.transform = Some(parse_transform_closure(assign.left.span(), *assign.right)?);
                         Ok(())
                     }
+                    "try_transform" => {
+                        let transform = parse_transform_closure(assign.left.span(), *assign.right)?;
+                        if transform.output.is_none() {
+                            return Err(Error::new(
+                                transform.span,
+                                "try_transform closure must declare its `Result` return type, e.g. \
+                                `try_transform = |s: &str| -> Result<u16, ParseIntError> { s.parse() }`",
+                            ));
+                        }
+                        self.try_transform = Some(transform);
+                        Ok(())
+                    }
+                    "validate" => {
+                        self.validate = Some(*assign.right);
+                        Ok(())
+                    }
+                    "each" => {
+                        self.each = Some(EachSettings {
+                            name: parse_setter_name(&assign.right)?,
+                            auto_into: None,
+                            key_value: None,
+                            span: assign.left.span(),
+                        });
+                        Ok(())
+                    }
+                    "reset" => {
+                        self.reset = Some(assign.left.span());
+                        self.reset_name = Some(parse_setter_name(&assign.right)?);
+                        Ok(())
+                    }
+                    "maybe" => {
+                        self.maybe = Some(assign.left.span());
+                        self.maybe_name = Some(parse_setter_name(&assign.right)?);
+                        Ok(())
+                    }
                     "prefix" => {
                         self.prefix = Some(expr_to_lit_string(&assign.right)?);
                         Ok(())
@@ -343,8 +781,57 @@ impl SetterSettings {
                     "into", auto_into, "calling into() on the argument", {};
                     "strip_option", strip_option, "putting the argument in Some(...)", {};
                     "strip_bool", strip_bool, "zero arguments setter, sets the field to true", {};
+                    "nested", nested, "set using a closure on the field type's builder", {};
+                    "overridable", overridable, "overridable", {};
+                    "reset", reset, "resettable", {};
+                    "maybe", maybe, "settable with an Option", {};
+                    "from_str", from_str, "settable from a string", {};
                 )
             }
+            syn::Expr::Call(call) if expr_to_single_string(&call.func).as_deref() == Some("strip_option") => {
+                if self.strip_option.is_some() {
+                    return Err(Error::new_spanned(
+                        &call.func,
+                        "Illegal setting - field is already putting the argument in Some(...)",
+                    ));
+                }
+                for arg in call.args {
+                    match &arg {
+                        syn::Expr::Assign(assign) if expr_to_single_string(&assign.left).as_deref() == Some("fallback") => {
+                            self.strip_option_fallback = Some(parse_setter_name(&assign.right)?);
+                        }
+                        _ => return Err(Error::new_spanned(arg, "Expected `fallback = ...`")),
+                    }
+                }
+                self.strip_option = Some(call.func.span());
+                Ok(())
+            }
+            syn::Expr::Call(call) if expr_to_single_string(&call.func).as_deref() == Some("each") => {
+                let mut name = None;
+                let mut auto_into = None;
+                let mut key_value = None;
+                for arg in call.args {
+                    match &arg {
+                        syn::Expr::Assign(assign) if expr_to_single_string(&assign.left).as_deref() == Some("name") => {
+                            name = Some(parse_setter_name(&assign.right)?);
+                        }
+                        syn::Expr::Path(path) if path_to_single_string(&path.path).as_deref() == Some("into") => {
+                            auto_into = Some(path.span());
+                        }
+                        syn::Expr::Path(path) if path_to_single_string(&path.path).as_deref() == Some("key_value") => {
+                            key_value = Some(path.span());
+                        }
+                        _ => return Err(Error::new_spanned(arg, "Expected `name = ...`, `into` or `key_value`")),
+                    }
+                }
+                self.each = Some(EachSettings {
+                    name: name.ok_or_else(|| Error::new_spanned(&call.func, "each(...) requires `name = ...`"))?,
+                    auto_into,
+                    key_value,
+                    span: call.func.span(),
+                });
+                Ok(())
+            }
             syn::Expr::Unary(syn::ExprUnary {
                 op: syn::UnOp::Not(_),
                 expr,
@@ -358,6 +845,10 @@ impl SetterSettings {
                             self.doc = None;
                             Ok(())
                         }
+                        "name" => {
+                            self.name = None;
+                            Ok(())
+                        }
                         "skip" => {
                             self.skip = None;
                             Ok(())
@@ -368,12 +859,43 @@ impl SetterSettings {
                         }
                         "strip_option" => {
                             self.strip_option = None;
+                            self.strip_option_fallback = None;
                             Ok(())
                         }
                         "strip_bool" => {
                             self.strip_bool = None;
                             Ok(())
                         }
+                        "nested" => {
+                            self.nested = None;
+                            Ok(())
+                        }
+                        "overridable" => {
+                            self.overridable = None;
+                            Ok(())
+                        }
+                        "reset" => {
+                            self.reset = None;
+                            self.reset_name = None;
+                            Ok(())
+                        }
+                        "maybe" => {
+                            self.maybe = None;
+                            self.maybe_name = None;
+                            Ok(())
+                        }
+                        "validate" => {
+                            self.validate = None;
+                            Ok(())
+                        }
+                        "from_str" => {
+                            self.from_str = None;
+                            Ok(())
+                        }
+                        "each" => {
+                            self.each = None;
+                            Ok(())
+                        }
                         _ => Err(Error::new_spanned(path, "Unknown setting".to_owned())),
                     }
                 } else {
@@ -385,13 +907,76 @@ impl SetterSettings {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    /// Exactly one of the group's fields must be set.
+    ExactlyOne,
+    /// At least one of the group's fields must be set.
+    AtLeastOne,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupMembership {
+    pub name: syn::Ident,
+    /// The kind of the group. Only needs to be given on one of its fields.
+    pub kind: Option<GroupKind>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EachSettings {
+    pub name: syn::Ident,
+    pub auto_into: Option<Span>,
+    /// Whether the item setter accepts a key and a value, set with `each(name = ..., key_value)`.
+    pub key_value: Option<Span>,
+    span: Span,
+}
+
+fn parse_setter_name(expr: &syn::Expr) -> Result<syn::Ident, Error> {
+    let name = if let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(name),
+        ..
+    }) = expr
+    {
+        name.value()
+    } else {
+        expr_to_single_string(expr).ok_or_else(|| Error::new_spanned(expr, "Expected identifier or string"))?
+    };
+    syn::parse_str(&name).map_err(|e| Error::new_spanned(expr, e))
+}
+
 #[derive(Debug, Clone)]
 pub struct Transform {
     pub params: Vec<(syn::Pat, syn::Type)>,
     pub body: syn::Expr,
+    pub output: Option<syn::Type>,
     span: Span,
 }
 
+impl Transform {
+    /// The `E` of a closure declared as returning `Result<T, E>` - `None` for other return types,
+    /// like aliases such as `io::Result<T>`.
+    pub fn try_error_type(&self) -> Option<&syn::Type> {
+        let syn::Type::Path(type_path) = self.output.as_ref()? else {
+            return None;
+        };
+        let segment = type_path.path.segments.last()?;
+        if segment.ident != "Result" {
+            return None;
+        }
+        let syn::PathArguments::AngleBracketed(generic_params) = &segment.arguments else {
+            return None;
+        };
+        let mut types = generic_params.args.iter().filter_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        });
+        match (types.next(), types.next(), types.next()) {
+            (Some(_), Some(error), None) => Some(error),
+            _ => None,
+        }
+    }
+}
+
 fn parse_transform_closure(span: Span, expr: syn::Expr) -> Result<Transform, Error> {
     let closure = match expr {
         syn::Expr::Closure(closure) => closure,
@@ -416,6 +1001,10 @@ fn parse_transform_closure(span: Span, expr: syn::Expr) -> Result<Transform, Err
     Ok(Transform {
         params,
         body: *closure.body,
+        output: match closure.output {
+            syn::ReturnType::Default => None,
+            syn::ReturnType::Type(_, ty) => Some(*ty),
+        },
         span,
     })
 }
@@ -6,7 +6,10 @@ mod field_info;
 mod struct_info;
 mod util;
 
-#[proc_macro_derive(TypedBuilder, attributes(builder))]
+// With serde, `#[serde(rename = "...")]` is also read from the fields - and must be accepted even
+// when serde's own derive is not used on the type.
+#[cfg_attr(feature = "serde", proc_macro_derive(TypedBuilder, attributes(builder, serde)))]
+#[cfg_attr(not(feature = "serde"), proc_macro_derive(TypedBuilder, attributes(builder)))]
 pub fn derive_typed_builder(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     match impl_my_derive(&input) {
@@ -20,29 +23,82 @@ fn impl_my_derive(ast: &syn::DeriveInput) -> Result<TokenStream, Error> {
         syn::Data::Struct(data) => match &data.fields {
             syn::Fields::Named(fields) => {
                 let struct_info = struct_info::StructInfo::new(ast, fields.named.iter())?;
-                let builder_creation = struct_info.builder_creation_impl()?;
-                let fields = struct_info
-                    .included_fields()
-                    .map(|f| struct_info.field_impl(f))
-                    .collect::<Result<TokenStream, _>>()?;
-                let required_fields = struct_info
-                    .included_fields()
-                    .filter(|f| f.builder_attr.default.is_none())
-                    .map(|f| struct_info.required_field_impl(f));
-                let build_method = struct_info.build_method_impl();
-
-                quote! {
-                    #builder_creation
-                    #fields
-                    #(#required_fields)*
-                    #build_method
-                }
+                impl_struct_info(&struct_info)?
+            }
+            syn::Fields::Unnamed(fields) => {
+                let struct_info = struct_info::StructInfo::new(ast, fields.unnamed.iter())?;
+                impl_struct_info(&struct_info)?
             }
-            syn::Fields::Unnamed(_) => return Err(Error::new(ast.span(), "TypedBuilder is not supported for tuple structs")),
             syn::Fields::Unit => return Err(Error::new(ast.span(), "TypedBuilder is not supported for unit structs")),
         },
-        syn::Data::Enum(_) => return Err(Error::new(ast.span(), "TypedBuilder is not supported for enums")),
+        syn::Data::Enum(data) => {
+            let enum_builder_attr = struct_info::TypeBuilderAttr::new(&ast.attrs)?;
+            for (caption, name) in [
+                ("builder_method", &enum_builder_attr.builder_method.name),
+                ("builder_type", &enum_builder_attr.builder_type.name),
+            ] {
+                if let Some(name) = name {
+                    return Err(Error::new_spanned(
+                        name,
+                        format!(
+                            "{}(name = ...) must be set on the enum's variants, not on the enum itself",
+                            caption
+                        ),
+                    ));
+                }
+            }
+            let mut variants = Vec::new();
+            for variant in data.variants.iter() {
+                // Only struct-like variants get a builder - unit and tuple variants are left alone.
+                if let syn::Fields::Named(fields) = &variant.fields {
+                    let struct_info =
+                        struct_info::StructInfo::new_for_variant(ast, variant, &enum_builder_attr, fields.named.iter())?;
+                    variants.push(impl_struct_info(&struct_info)?);
+                }
+            }
+            quote!(#(#variants)*)
+        }
         syn::Data::Union(_) => return Err(Error::new(ast.span(), "TypedBuilder is not supported for unions")),
     };
     Ok(data)
 }
+
+fn impl_struct_info(struct_info: &struct_info::StructInfo) -> Result<TokenStream, Error> {
+    let builder_creation = struct_info.builder_creation_impl()?;
+    let fields = struct_info
+        .setter_fields()
+        .map(|f| struct_info.field_impl(f))
+        .collect::<Result<TokenStream, _>>()?;
+    let resets = struct_info
+        .setter_fields()
+        .filter(|f| f.builder_attr.setter.reset.is_some())
+        .map(|f| struct_info.reset_impl(f));
+    let flatten_hosts = struct_info
+        .included_fields()
+        .filter(|f| f.builder_attr.flatten.is_some())
+        .map(|f| struct_info.flatten_host_impl(f));
+    let required_fields = struct_info
+        .setter_fields()
+        .filter(|f| f.builder_attr.default.is_none())
+        .map(|f| struct_info.required_field_impl(f));
+    let build_method = struct_info.build_method_impl()?;
+    let into_builder = struct_info.to_builder_methods_impl()?;
+    let flatten_setters = struct_info.flatten_setters_impl()?;
+    let dyn_builder = struct_info.dyn_builder_impl()?;
+    let deserialize = struct_info.deserialize_impl()?;
+    let with_env = struct_info.with_env_impl()?;
+
+    Ok(quote! {
+        #builder_creation
+        #fields
+        #(#resets)*
+        #(#flatten_hosts)*
+        #(#required_fields)*
+        #build_method
+        #into_builder
+        #flatten_setters
+        #dyn_builder
+        #deserialize
+        #with_env
+    })
+}
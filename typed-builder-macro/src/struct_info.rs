@@ -1,23 +1,40 @@
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{quote, ToTokens};
-use syn::parse::Error;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{
+    parse::{Error, Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+};
 
-use crate::field_info::{FieldBuilderAttr, FieldInfo};
+use crate::field_info::{EachSettings, FieldBuilderAttr, FieldInfo, GroupKind};
 use crate::util::{
-    apply_subsections, empty_type, empty_type_tuple, expr_to_single_string, first_visibility, modify_types_generics_hack,
-    path_to_single_string, public_visibility, strip_raw_ident_prefix, type_tuple,
+    empty_type, empty_type_tuple, expr_to_single_string, first_visibility, local_idents, modify_types_generics_hack,
+    path_to_single_string, public_visibility, strip_raw_ident_prefix, to_identifier_text, to_snake_case, type_tuple,
 };
 
 #[derive(Debug)]
 pub struct StructInfo<'a> {
     pub vis: &'a syn::Visibility,
     pub name: &'a syn::Ident,
+    /// The enum variant this builder constructs, when deriving for an enum.
+    pub variant: Option<&'a syn::Ident>,
     pub generics: &'a syn::Generics,
     pub fields: Vec<FieldInfo<'a>>,
+    /// Whether the fields are unnamed, and the built value is constructed positionally.
+    pub is_tuple: bool,
 
     pub builder_attr: TypeBuilderAttr<'a>,
     pub builder_name: syn::Ident,
-    pub core: syn::Ident,
+    /// The groups formed by the fields' `group(...)` settings.
+    pub groups: Vec<FieldGroup>,
+}
+
+#[derive(Debug)]
+pub struct FieldGroup {
+    pub name: syn::Ident,
+    pub kind: GroupKind,
+    /// The ordinals of the group's fields.
+    pub members: Vec<usize>,
 }
 
 impl<'a> StructInfo<'a> {
@@ -25,6 +42,11 @@ impl<'a> StructInfo<'a> {
         self.fields.iter().filter(|f| f.builder_attr.setter.skip.is_none())
     }
 
+    /// The included fields that get their own setters - i.e. all of them except the `flatten` ones.
+    pub fn setter_fields(&self) -> impl Iterator<Item = &FieldInfo<'a>> {
+        self.included_fields().filter(|f| f.builder_attr.flatten.is_none())
+    }
+
     pub fn new(ast: &'a syn::DeriveInput, fields: impl Iterator<Item = &'a syn::Field>) -> Result<StructInfo<'a>, Error> {
         let builder_attr = TypeBuilderAttr::new(&ast.attrs)?;
         let builder_name = builder_attr
@@ -32,17 +54,534 @@ impl<'a> StructInfo<'a> {
             .get_name()
             .map(|name| strip_raw_ident_prefix(name.to_string()))
             .unwrap_or_else(|| strip_raw_ident_prefix(format!("{}Builder", ast.ident)));
+        Self::with_builder_attr(ast, None, builder_attr, builder_name, fields)
+    }
+
+    /// Create the info for building a single struct-like variant of an enum.
+    ///
+    /// `enum_builder_attr` holds the settings from the enum's own `#[builder(...)]` attributes, which
+    /// the variant's attributes are applied on top of.
+    pub fn new_for_variant(
+        ast: &'a syn::DeriveInput,
+        variant: &'a syn::Variant,
+        enum_builder_attr: &TypeBuilderAttr<'a>,
+        fields: impl Iterator<Item = &'a syn::Field>,
+    ) -> Result<StructInfo<'a>, Error> {
+        let builder_attr = enum_builder_attr.clone().with(&variant.attrs)?;
+        let builder_name = builder_attr
+            .builder_type
+            .get_name()
+            .map(|name| strip_raw_ident_prefix(name.to_string()))
+            .unwrap_or_else(|| strip_raw_ident_prefix(format!("{}{}Builder", ast.ident, variant.ident)));
+        Self::with_builder_attr(ast, Some(&variant.ident), builder_attr, builder_name, fields)
+    }
+
+    fn with_builder_attr(
+        ast: &'a syn::DeriveInput,
+        variant: Option<&'a syn::Ident>,
+        builder_attr: TypeBuilderAttr<'a>,
+        builder_name: String,
+        fields: impl Iterator<Item = &'a syn::Field>,
+    ) -> Result<StructInfo<'a>, Error> {
+        let fields = fields.collect::<Vec<_>>();
+        let is_tuple = fields.iter().any(|f| f.ident.is_none());
+        let fields = fields
+            .into_iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let mut field = FieldInfo::new(i, f, builder_attr.field_defaults.clone())?;
+                if builder_attr.deserialize.is_some() {
+                    field.parse_serde_attrs(&f.attrs)?;
+                }
+                Ok(field)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        if let (Some(default_from_ctx), None) = (
+            fields.iter().find_map(|f| f.builder_attr.default_from_ctx.as_ref()),
+            &builder_attr.build_method.context,
+        ) {
+            return Err(Error::new_spanned(
+                default_from_ctx,
+                "default_from_ctx requires the context type to be set with `build_method(context = ...)`",
+            ));
+        }
+        if let (Some(default_async), None) = (
+            fields.iter().find_map(|f| f.builder_attr.default_async),
+            builder_attr.build_method.asyncness,
+        ) {
+            return Err(Error::new(default_async, "default_async requires `build_method(async)`"));
+        }
+        if let (Some(asyncness), Some(dynamic)) = (builder_attr.build_method.asyncness, builder_attr.dynamic) {
+            let mut error = Error::new(dynamic, "dynamic is not supported with an async build method");
+            error.combine(Error::new(asyncness, "async set here"));
+            return Err(error);
+        }
+        let groups = Self::collect_groups(&fields)?;
+        Self::check_constraints(&fields, &groups, &builder_attr)?;
         Ok(StructInfo {
             vis: &ast.vis,
             name: &ast.ident,
+            variant,
             generics: &ast.generics,
-            fields: fields
-                .enumerate()
-                .map(|(i, f)| FieldInfo::new(i, f, builder_attr.field_defaults.clone()))
-                .collect::<Result<_, _>>()?,
+            is_tuple,
+            fields,
             builder_attr,
             builder_name: syn::Ident::new(&builder_name, proc_macro2::Span::call_site()),
-            core: syn::Ident::new(&format!("{}_core", builder_name), proc_macro2::Span::call_site()),
+            groups,
+        })
+    }
+
+    fn collect_groups(fields: &[FieldInfo]) -> Result<Vec<FieldGroup>, Error> {
+        let mut groups: Vec<(syn::Ident, Option<GroupKind>, Vec<usize>)> = Vec::new();
+        for field in fields {
+            for membership in &field.builder_attr.groups {
+                if let Some((_, kind, members)) = groups.iter_mut().find(|(name, ..)| *name == membership.name) {
+                    match (*kind, membership.kind) {
+                        (Some(kind), Some(other_kind)) if kind != other_kind => {
+                            return Err(Error::new_spanned(
+                                &membership.name,
+                                format_args!("group {} is given different kinds", membership.name),
+                            ));
+                        }
+                        (None, other_kind) => *kind = other_kind,
+                        _ => {}
+                    }
+                    members.push(field.ordinal);
+                } else {
+                    groups.push((membership.name.clone(), membership.kind, vec![field.ordinal]));
+                }
+            }
+        }
+        let groups = groups
+            .into_iter()
+            .map(|(name, kind, members)| {
+                let kind = kind.ok_or_else(|| {
+                    Error::new_spanned(
+                        &name,
+                        format_args!(
+                            "the kind of group {} must be set on one of its fields, e.g. `group({}, exactly_one)`",
+                            name, name
+                        ),
+                    )
+                })?;
+                Ok(FieldGroup { name, kind, members })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(groups)
+    }
+
+    /// Check the fields referred to by `requires` and `conflicts_with`, and the settings that can't
+    /// be combined with constraints between the fields.
+    fn check_constraints(fields: &[FieldInfo], groups: &[FieldGroup], builder_attr: &TypeBuilderAttr) -> Result<(), Error> {
+        for field in fields {
+            for (caption, name) in field
+                .builder_attr
+                .requires
+                .iter()
+                .map(|name| ("requires", name))
+                .chain(field.builder_attr.conflicts_with.iter().map(|name| ("conflicts_with", name)))
+            {
+                let target = fields
+                    .iter()
+                    .find(|f| f.name == *name && f.builder_attr.setter.skip.is_none())
+                    .ok_or_else(|| Error::new_spanned(name, format_args!("no field named {} to set", name)))?;
+                if target.ordinal == field.ordinal {
+                    return Err(Error::new_spanned(
+                        name,
+                        format_args!("{} can't refer to the field itself", caption),
+                    ));
+                }
+                for (setting, is_set) in [
+                    ("maybe", target.builder_attr.setter.maybe.is_some()),
+                    ("env", target.builder_attr.env.is_some()),
+                    ("flatten", target.builder_attr.flatten.is_some()),
+                ] {
+                    if is_set {
+                        return Err(Error::new_spanned(
+                            name,
+                            format_args!("{} can't refer to a field with {}", caption, setting),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let constraint_name = (groups.first().map(|group| &group.name)).or_else(|| {
+            fields
+                .iter()
+                .find_map(|f| f.builder_attr.requires.first().or(f.builder_attr.conflicts_with.first()))
+        });
+        let Some(constraint_name) = constraint_name else {
+            return Ok(());
+        };
+        for (caption, span) in [
+            ("into_builder", builder_attr.into_builder),
+            ("dynamic", builder_attr.dynamic),
+            ("deserialize", builder_attr.deserialize),
+            ("flattenable", builder_attr.flattenable),
+        ] {
+            if let Some(span) = span {
+                let mut error = Error::new(
+                    span,
+                    format_args!("{} is not supported with groups, requires or conflicts_with", caption),
+                );
+                error.combine(Error::new_spanned(constraint_name, "constraint set here"));
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    fn field_named(&self, name: &syn::Ident) -> &FieldInfo<'a> {
+        self.included_fields()
+            .find(|f| f.name == *name)
+            .expect("fields referred to by constraints are checked when the struct info is created")
+    }
+
+    /// The constraints between the fields set by groups, `requires` and `conflicts_with`.
+    fn field_constraints(&self) -> Vec<FieldConstraint<'_, 'a>> {
+        let names = |fields: &[&FieldInfo]| {
+            fields
+                .iter()
+                .map(|f| strip_raw_ident_prefix(f.name.to_string()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut constraints = Vec::new();
+        for group in &self.groups {
+            let members = self
+                .included_fields()
+                .filter(|f| group.members.contains(&f.ordinal))
+                .collect::<Vec<_>>();
+            let (description, allowed_states, violation) = match group.kind {
+                GroupKind::ExactlyOne => (
+                    format!("Exactly one of: {}", names(&members)),
+                    (0..members.len())
+                        .map(|i| members.iter().enumerate().map(|(j, f)| (*f, i == j)).collect())
+                        .collect(),
+                    Some(ConstraintViolation {
+                        note: format!("Missing one of: {}", names(&members)),
+                        state: members.iter().map(|f| (*f, false)).collect(),
+                    }),
+                ),
+                // Each allowed state sets the first of the group's fields that is set, so that they
+                // don't overlap.
+                GroupKind::AtLeastOne => (
+                    format!("At least one of: {}", names(&members)),
+                    (0..members.len())
+                        .map(|i| members[..=i].iter().enumerate().map(|(j, f)| (*f, i == j)).collect())
+                        .collect(),
+                    Some(ConstraintViolation {
+                        note: format!("Missing one of: {}", names(&members)),
+                        state: members.iter().map(|f| (*f, false)).collect(),
+                    }),
+                ),
+            };
+            constraints.push(FieldConstraint {
+                description,
+                allowed_states,
+                violation,
+            });
+        }
+        for field in self.included_fields() {
+            let field_name = strip_raw_ident_prefix(field.name.to_string());
+            for name in &field.builder_attr.requires {
+                let other = self.field_named(name);
+                let other_name = strip_raw_ident_prefix(name.to_string());
+                constraints.push(FieldConstraint {
+                    description: format!("{} requires {}", field_name, other_name),
+                    allowed_states: vec![vec![(field, false)], vec![(field, true), (other, true)]],
+                    violation: Some(ConstraintViolation {
+                        note: format!("Missing field {} - required by {}", other_name, field_name),
+                        state: vec![(field, true), (other, false)],
+                    }),
+                });
+            }
+            for name in &field.builder_attr.conflicts_with {
+                let other = self.field_named(name);
+                constraints.push(FieldConstraint {
+                    description: format!("{} conflicts with {}", field_name, strip_raw_ident_prefix(name.to_string())),
+                    allowed_states: vec![vec![(field, false)], vec![(field, true), (other, false)]],
+                    // The setters of conflicting fields aren't available once one of them is set.
+                    violation: None,
+                });
+            }
+        }
+        constraints
+    }
+
+    fn constraint_trait_name(&self, constraint: &FieldConstraint) -> syn::Ident {
+        syn::Ident::new(
+            &format!(
+                "{}_Constraint_{}",
+                self.builder_name,
+                to_identifier_text(&constraint.description)
+            ),
+            Span::call_site(),
+        )
+    }
+
+    /// The hidden marker traits of the constraints between the fields, implemented for the builder
+    /// states that satisfy them and required by the build method - along with their names.
+    fn constraint_traits_impl(&self) -> (Vec<syn::Ident>, TokenStream) {
+        let StructInfo { ref builder_name, .. } = *self;
+        let generic_args = self.generic_args();
+        let mut trait_names = Vec::new();
+        let mut traits = Vec::new();
+        for constraint in self.field_constraints() {
+            let trait_name = self.constraint_trait_name(&constraint);
+            let FieldConstraint {
+                description,
+                allowed_states,
+                ..
+            } = constraint;
+            let impls = allowed_states.iter().map(|allowed_state| {
+                let mut generics = self.generics.clone();
+                let state = type_tuple(self.included_fields().map(|field| {
+                    match allowed_state.iter().find(|(f, _)| f.ordinal == field.ordinal) {
+                        Some((_, true)) => field.tuplized_type_ty_param(),
+                        Some((_, false)) => empty_type(),
+                        None => {
+                            generics.params.push(field.generic_ty_param());
+                            field.type_ident()
+                        }
+                    }
+                }));
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics #trait_name for #builder_name < #(#generic_args,)* #state > #where_clause {}
+                }
+            });
+            traits.push(quote! {
+                #[doc(hidden)]
+                #[doc = #description]
+                #[allow(dead_code, non_camel_case_types)]
+                pub trait #trait_name {}
+                #( #impls )*
+            });
+            trait_names.push(trait_name);
+        }
+        (trait_names, quote!(#( #traits )*))
+    }
+
+    /// Deprecated overloads of the build method for the builder states that violate the constraints
+    /// between the fields, which show the violation as the deprecation note. Each overload requires
+    /// the constraints before it to hold and the required fields to be set, so that they don't
+    /// overlap with each other or with the overloads for missing required fields.
+    fn constraint_violations_impl(&self, constraint_traits: &[syn::Ident]) -> TokenStream {
+        let StructInfo { ref builder_name, .. } = *self;
+        let generic_args = self.generic_args();
+        let build_method_visibility = self.build_method_visibility();
+        let constraints = self.field_constraints();
+        let mut result = TokenStream::new();
+        for (i, constraint) in constraints.iter().enumerate() {
+            let Some(ConstraintViolation {
+                note,
+                state: violating_state,
+            }) = &constraint.violation
+            else {
+                continue;
+            };
+            let error_type_name = syn::Ident::new(
+                &format!("{}_Error_{}", builder_name, to_identifier_text(note)),
+                Span::call_site(),
+            );
+            let fake_builds = self
+                .build_variants()
+                .filter_map(|with_ctx| {
+                    let mut generics = self.generics.clone();
+                    let mut state = Vec::new();
+                    for field in self.included_fields() {
+                        let is_required = !Self::has_default(field, with_ctx) && field.builder_attr.flatten.is_none();
+                        match violating_state.iter().find(|(f, _)| f.ordinal == field.ordinal) {
+                            // Missing required fields have their own overloads.
+                            Some((_, false)) if is_required => return None,
+                            Some((_, false)) => state.push(empty_type()),
+                            Some((_, true)) => state.push(field.tuplized_type_ty_param()),
+                            None if is_required => state.push(field.tuplized_type_ty_param()),
+                            None => {
+                                generics.params.push(field.generic_ty_param());
+                                state.push(field.type_ident());
+                            }
+                        }
+                    }
+                    let state = type_tuple(state.into_iter());
+                    for constraint_trait in &constraint_traits[..i] {
+                        generics
+                            .make_where_clause()
+                            .predicates
+                            .push(syn::parse_quote!(#builder_name < #(#generic_args,)* #state >: #constraint_trait));
+                    }
+                    let (impl_generics, _, where_clause) = generics.split_for_impl();
+                    let method_name = if with_ctx {
+                        self.build_with_method_name().to_token_stream()
+                    } else {
+                        self.build_method_name()
+                    };
+                    Some(quote! {
+                        #[doc(hidden)]
+                        #[allow(dead_code, non_camel_case_types, missing_docs, clippy::panic)]
+                        #[automatically_derived]
+                        impl #impl_generics #builder_name < #(#generic_args,)* #state > #where_clause {
+                            #[deprecated(
+                                note = #note
+                            )]
+                            #build_method_visibility fn #method_name(self, _: #error_type_name) -> ! {
+                                panic!()
+                            }
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            result.extend(quote! {
+                #[doc(hidden)]
+                #[allow(dead_code, non_camel_case_types, non_snake_case)]
+                #[allow(clippy::exhaustive_enums)]
+                pub enum #error_type_name {}
+                #( #fake_builds )*
+            });
+        }
+        result
+    }
+
+    /// The fields that must be unset for the setters of `field` to be available.
+    fn exclusive_fields(&self, field: &FieldInfo) -> Vec<&FieldInfo<'a>> {
+        self.included_fields()
+            .filter(|f| {
+                f.ordinal != field.ordinal
+                    && (self.groups.iter().any(|g| {
+                        g.kind == GroupKind::ExactlyOne && g.members.contains(&field.ordinal) && g.members.contains(&f.ordinal)
+                    }) || field.builder_attr.conflicts_with.contains(&f.name)
+                        || f.builder_attr.conflicts_with.contains(&field.name))
+            })
+            .collect()
+    }
+
+    fn is_exclusive_with(&self, field: &FieldInfo, other: &FieldInfo) -> bool {
+        self.exclusive_fields(field).iter().any(|f| f.ordinal == other.ordinal)
+    }
+
+    /// Deprecated overloads of the setters of `field` for the states where one of the fields it is
+    /// exclusive with is already set, which show the conflict as the deprecation note.
+    fn conflicting_setters_impl(&self, field: &FieldInfo, method_names: &[&Ident]) -> TokenStream {
+        let StructInfo { ref builder_name, .. } = *self;
+        let generic_args = self.generic_args();
+        let exclusive_fields = self.exclusive_fields(field);
+        let field_name = strip_raw_ident_prefix(field.name.to_string());
+        exclusive_fields
+            .iter()
+            .enumerate()
+            .map(|(i, conflicting)| {
+                let mut generics = self.generics.clone();
+                let state = type_tuple(self.included_fields().map(|f| {
+                    if f.ordinal == field.ordinal {
+                        empty_type()
+                    } else if f.ordinal == conflicting.ordinal {
+                        f.tuplized_type_ty_param()
+                    } else if exclusive_fields[..i].iter().any(|e| e.ordinal == f.ordinal) {
+                        // Conflicts with earlier fields get their own overloads.
+                        empty_type()
+                    } else {
+                        generics.params.push(f.generic_ty_param());
+                        f.type_ident()
+                    }
+                }));
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+                let conflicting_name = strip_raw_ident_prefix(conflicting.name.to_string());
+                let message = format!("Conflicting field {} - {} is already set", field_name, conflicting_name);
+                let error_type_name = syn::Ident::new(
+                    &format!("{}_Error_Conflicting_field_{}_{}", builder_name, field_name, conflicting_name),
+                    Span::call_site(),
+                );
+                quote! {
+                    #[doc(hidden)]
+                    #[allow(dead_code, non_camel_case_types, non_snake_case)]
+                    #[allow(clippy::exhaustive_enums)]
+                    pub enum #error_type_name {}
+                    #[doc(hidden)]
+                    #[allow(dead_code, non_camel_case_types, missing_docs)]
+                    #[automatically_derived]
+                    impl #impl_generics #builder_name < #(#generic_args,)* #state > #where_clause {
+                        #(
+                            #[deprecated(
+                                note = #message
+                            )]
+                            pub fn #method_names (self, _: #error_type_name) -> Self {
+                                self
+                            }
+                        )*
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The generic parameters of the struct, as arguments (without their bounds).
+    fn generic_args(&self) -> Vec<TokenStream> {
+        self.generics
+            .params
+            .iter()
+            .map(|generic_param| match generic_param {
+                syn::GenericParam::Type(type_param) => type_param.ident.to_token_stream(),
+                syn::GenericParam::Lifetime(lifetime_def) => lifetime_def.lifetime.to_token_stream(),
+                syn::GenericParam::Const(const_param) => const_param.ident.to_token_stream(),
+            })
+            .collect()
+    }
+
+    /// The types the builder's `PhantomData` holds, so that it uses all the generic parameters of the
+    /// struct.
+    fn phantom_generics(&self) -> Vec<TokenStream> {
+        self.generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Lifetime(lifetime) => {
+                    let lifetime = &lifetime.lifetime;
+                    Some(quote!(&#lifetime ()))
+                }
+                syn::GenericParam::Type(ty) => {
+                    let ty = &ty.ident;
+                    Some(ty.to_token_stream())
+                }
+                syn::GenericParam::Const(_cnst) => None,
+            })
+            .collect()
+    }
+
+    /// Whether the struct's builder can be exposed through the [`HasTypedBuilder`] trait and the
+    /// setters trait used for flattening. Not done when the user restricted the visibility of the
+    /// builder, since that would either leak it or fail to compile.
+    fn exposes_builder(&self) -> bool {
+        self.variant.is_none() && self.builder_attr.builder_method.vis.is_none() && self.builder_attr.builder_type.vis.is_none()
+    }
+
+    /// Whether the field's setter can also be called when the field is already set, replacing its
+    /// value.
+    fn is_overridable(&self, field: &FieldInfo) -> bool {
+        field.builder_attr.setter.overridable.is_some()
+    }
+
+    /// The name used for the built type in generated documentation - `Foo` for structs and
+    /// `Foo::Variant` for enum variants.
+    fn target_name(&self) -> String {
+        if let Some(variant) = self.variant {
+            format!("{}::{}", self.name, variant)
+        } else {
+            self.name.to_string()
+        }
+    }
+
+    fn builder_method_name(&self) -> TokenStream {
+        self.builder_attr.builder_method.get_name().unwrap_or_else(|| {
+            if let Some(variant) = self.variant {
+                let method_name = format!("{}_builder", to_snake_case(&strip_raw_ident_prefix(variant.to_string())));
+                Ident::new(&method_name, Span::call_site()).to_token_stream()
+            } else {
+                quote!(builder)
+            }
         })
     }
 
@@ -54,7 +593,16 @@ impl<'a> StructInfo<'a> {
             ..
         } = *self;
         let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
-        let empties_tuple = type_tuple(self.included_fields().map(|_| empty_type()));
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let empties_tuple = type_tuple(self.included_fields().map(|f| f.initial_state_type(crate_module_path)));
+        let empties_value = self.included_fields().map(|f| {
+            if f.builder_attr.flatten.is_some() {
+                let ty = f.ty;
+                quote!(<#ty as #crate_module_path::HasTypedBuilder>::create_builder())
+            } else {
+                quote!(())
+            }
+        });
         let mut all_fields_param_type: syn::TypeParam =
             syn::Ident::new("TypedBuilderFields", proc_macro2::Span::call_site()).into();
         let all_fields_param = syn::GenericParam::Type(all_fields_param_type.clone());
@@ -67,19 +615,9 @@ impl<'a> StructInfo<'a> {
         let generics_with_empty = modify_types_generics_hack(&ty_generics, |args| {
             args.push(syn::GenericArgument::Type(empties_tuple.clone().into()));
         });
-        let phantom_generics = self.generics.params.iter().filter_map(|param| match param {
-            syn::GenericParam::Lifetime(lifetime) => {
-                let lifetime = &lifetime.lifetime;
-                Some(quote!(&#lifetime ()))
-            }
-            syn::GenericParam::Type(ty) => {
-                let ty = &ty.ident;
-                Some(ty.to_token_stream())
-            }
-            syn::GenericParam::Const(_cnst) => None,
-        });
+        let phantom_generics = self.phantom_generics();
 
-        let builder_method_name = self.builder_attr.builder_method.get_name().unwrap_or_else(|| quote!(builder));
+        let builder_method_name = self.builder_method_name();
         let builder_method_visibility = first_visibility(&[
             self.builder_attr.builder_method.vis.as_ref(),
             self.builder_attr.builder_type.vis.as_ref(),
@@ -92,7 +630,7 @@ impl<'a> StructInfo<'a> {
                 On the builder, call {setters} to set the values of the fields.
                 Finally, call `.build()` to create the instance of `{name}`.
                 ",
-                name = self.name,
+                name = self.target_name(),
                 setters = {
                     let mut result = String::new();
                     let mut is_first = true;
@@ -103,7 +641,11 @@ impl<'a> StructInfo<'a> {
                         } else {
                             write!(&mut result, ", ").unwrap();
                         }
-                        write!(&mut result, "`.{}(...)`", field.name).unwrap();
+                        if field.builder_attr.flatten.is_some() {
+                            write!(&mut result, "the setters of `{}`", field.ty.to_token_stream()).unwrap();
+                            continue;
+                        }
+                        write!(&mut result, "`.{}(...)`", field.setter_method_name()).unwrap();
                         if field.builder_attr.default.is_some() {
                             write!(&mut result, "(optional)").unwrap();
                         }
@@ -117,14 +659,31 @@ impl<'a> StructInfo<'a> {
         let builder_type_doc = if self.builder_attr.doc {
             self.builder_attr.builder_type.get_doc_or(|| {
                 format!(
-                    "Builder for [`{name}`] instances.\n\nSee [`{name}::builder()`] for more info.",
-                    name = name
+                    "Builder for [`{target}`] instances.\n\nSee [`{name}::{builder_method}()`] for more info.",
+                    target = self.target_name(),
+                    name = name,
+                    builder_method = builder_method_name,
                 )
             })
         } else {
             quote!(#[doc(hidden)])
         };
 
+        let has_typed_builder_impl = if self.exposes_builder() {
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics #crate_module_path::HasTypedBuilder for #name #ty_generics #where_clause {
+                    type Builder = #builder_name #generics_with_empty;
+
+                    fn create_builder() -> Self::Builder {
+                        Self::#builder_method_name()
+                    }
+                }
+            }
+        } else {
+            quote!()
+        };
+
         let (b_generics_impl, b_generics_ty, b_generics_where_extras_predicates) = b_generics.split_for_impl();
         let mut b_generics_where: syn::WhereClause = syn::parse2(quote! {
             where TypedBuilderFields: Clone
@@ -140,7 +699,7 @@ impl<'a> StructInfo<'a> {
                 #[allow(dead_code, clippy::default_trait_access)]
                 #builder_method_visibility fn #builder_method_name() -> #builder_name #generics_with_empty {
                     #builder_name {
-                        fields: #empties_tuple,
+                        fields: (#(#empties_value,)*),
                         phantom: ::core::default::Default::default(),
                     }
                 }
@@ -164,27 +723,29 @@ impl<'a> StructInfo<'a> {
                     }
                 }
             }
+
+            #has_typed_builder_impl
         })
     }
 
     pub fn field_impl(&self, field: &FieldInfo) -> Result<TokenStream, Error> {
         let StructInfo { ref builder_name, .. } = *self;
 
+        let overridable = self.is_overridable(field);
         let descructuring = self.included_fields().map(|f| {
-            if f.ordinal == field.ordinal {
-                quote!(())
+            if f.ordinal != field.ordinal {
+                f.name.to_token_stream()
+            } else if overridable {
+                quote!(_)
             } else {
-                let name = f.name;
-                name.to_token_stream()
+                quote!(())
             }
         });
-        let reconstructing = self.included_fields().map(|f| f.name);
+        let reconstructing = self.included_fields().map(|f| &f.name).collect::<Vec<_>>();
 
-        let &FieldInfo {
-            name: field_name,
-            ty: field_type,
-            ..
-        } = field;
+        let FieldInfo {
+            name: ref field_name, ..
+        } = *field;
         let mut ty_generics: Vec<syn::GenericArgument> = self
             .generics
             .params
@@ -207,8 +768,17 @@ impl<'a> StructInfo<'a> {
             let mut generics = self.generics.clone();
             for f in self.included_fields() {
                 if f.ordinal == field.ordinal {
-                    ty_generics_tuple.elems.push_value(empty_type());
+                    if overridable {
+                        // The setter can be called whether or not the field is already set.
+                        generics.params.push(f.generic_ty_param());
+                        ty_generics_tuple.elems.push_value(f.type_ident());
+                    } else {
+                        ty_generics_tuple.elems.push_value(empty_type());
+                    }
                     target_generics_tuple.elems.push_value(f.tuplized_type_ty_param());
+                } else if self.is_exclusive_with(field, f) {
+                    ty_generics_tuple.elems.push_value(empty_type());
+                    target_generics_tuple.elems.push_value(empty_type());
                 } else {
                     generics.params.push(f.generic_ty_param());
                     let generic_argument: syn::Type = f.type_ident();
@@ -227,31 +797,25 @@ impl<'a> StructInfo<'a> {
         let doc = field.builder_attr.setter.doc.as_ref().map(|doc| quote!(#[doc = #doc]));
         let deprecated = &field.builder_attr.deprecated;
 
-        // NOTE: both auto_into and strip_option affect `arg_type` and `arg_expr`, but the order of
-        // nesting is different so we have to do this little dance.
-        let arg_type = if field.builder_attr.setter.strip_option.is_some() && field.builder_attr.setter.transform.is_none() {
-            field
-                .type_from_inside_option()
-                .ok_or_else(|| Error::new_spanned(field_type, "can't `strip_option` - field is not `Option<...>`"))?
+        let (params, arg_expr) = self.setter_value(field)?;
+        let param_list = params.iter().map(|(pat, ty)| quote!(#pat: #ty));
+
+        let target_builder = quote!(#builder_name <#( #target_generics ),*>);
+        let (return_type, wrap_result) = if let Some(error) = self.setter_error_type(field) {
+            (quote!(::core::result::Result<#target_builder, #error>), true)
         } else {
-            field_type
+            (target_builder.clone(), false)
         };
-        let (arg_type, arg_expr) = if field.builder_attr.setter.auto_into.is_some() {
-            (quote!(impl ::core::convert::Into<#arg_type>), quote!(#field_name.into()))
-        } else {
-            (arg_type.to_token_stream(), field_name.to_token_stream())
+        let new_builder = quote! {
+            #builder_name {
+                fields: ( #(#reconstructing,)* ),
+                phantom: self.phantom,
+            }
         };
-
-        let (param_list, arg_expr) = if field.builder_attr.setter.strip_bool.is_some() {
-            (quote!(), quote!(true))
-        } else if let Some(transform) = &field.builder_attr.setter.transform {
-            let params = transform.params.iter().map(|(pat, ty)| quote!(#pat: #ty));
-            let body = &transform.body;
-            (quote!(#(#params),*), quote!({ #body }))
-        } else if field.builder_attr.setter.strip_option.is_some() {
-            (quote!(#field_name: #arg_type), quote!(Some(#arg_expr)))
+        let new_builder = if wrap_result {
+            quote!(::core::result::Result::Ok(#new_builder))
         } else {
-            (quote!(#field_name: #arg_type), arg_expr)
+            new_builder
         };
 
         let repeated_fields_error_type_name = syn::Ident::new(
@@ -264,40 +828,1572 @@ impl<'a> StructInfo<'a> {
         );
         let repeated_fields_error_message = format!("Repeated field {}", field_name);
 
-        let method_name = field.setter_method_name();
+        let method_name = field.setter_method_name();
+        let method_generics = self.setter_method_generics(field);
+
+        let conflicting_setters = {
+            let mut method_names = Vec::new();
+            match &field.builder_attr.setter.each {
+                // The item setter replaces the bulk setter.
+                Some(each) if each.name == method_name => {}
+                _ => method_names.push(&method_name),
+            }
+            method_names.extend(&field.builder_attr.setter.strip_option_fallback);
+            method_names.extend(field.builder_attr.setter.each.as_ref().map(|each| &each.name));
+            self.conflicting_setters_impl(field, &method_names)
+        };
+
+        let each_setter = if let Some(each) = &field.builder_attr.setter.each {
+            let each_setter = self.each_setter_impl(field, each)?;
+            if each.name == method_name {
+                // The item setter replaces the bulk setter.
+                return Ok(quote! {
+                    #each_setter
+                    #conflicting_setters
+                });
+            }
+            each_setter
+        } else {
+            quote!()
+        };
+
+        let descructuring = descructuring.collect::<Vec<_>>();
+        let fallback_setter = field.builder_attr.setter.strip_option_fallback.as_ref().map(|fallback_name| {
+            let field_type = field.ty;
+            quote! {
+                #deprecated
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #fallback_name (self, #field_name: #field_type) -> #target_builder {
+                    let #field_name = (#field_name,);
+                    let ( #(#descructuring,)* ) = self.fields;
+                    #builder_name {
+                        fields: ( #(#reconstructing,)* ),
+                        phantom: self.phantom,
+                    }
+                }
+            }
+        });
+
+        let maybe_method_name = field.maybe_method_name();
+        let maybe_generics = {
+            let mut maybe_generics = ty_generics.clone();
+            let mut maybe_state = empty_type_tuple();
+            for f in self.included_fields() {
+                if f.ordinal == field.ordinal {
+                    maybe_state
+                        .elems
+                        .push(f.maybe_state_type(&self.builder_attr.crate_module_path));
+                } else {
+                    maybe_state.elems.push(f.type_ident());
+                }
+                maybe_state.elems.push_punct(Default::default());
+            }
+            *maybe_generics.last_mut().unwrap() = syn::GenericArgument::Type(maybe_state.into());
+            maybe_generics
+        };
+        let maybe_setter = if field.builder_attr.setter.maybe.is_some() {
+            let crate_module_path = &self.builder_attr.crate_module_path;
+            let param_type = self.maybe_setter_param_type(field)?;
+            let value = if field.builder_attr.setter.strip_option.is_some() {
+                quote!(#field_name.map(::core::option::Option::Some))
+            } else {
+                field_name.to_token_stream()
+            };
+            Some(quote! {
+                #deprecated
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #maybe_method_name (self, #field_name: #param_type) -> #builder_name < #( #maybe_generics ),* > {
+                    let #field_name = #crate_module_path::Maybe(#value);
+                    let ( #(#descructuring,)* ) = self.fields;
+                    #builder_name {
+                        fields: ( #(#reconstructing,)* ),
+                        phantom: self.phantom,
+                    }
+                }
+            })
+        } else {
+            None
+        };
+
+        let setter = quote! {
+            #each_setter
+            #[allow(dead_code, non_camel_case_types, missing_docs)]
+            #[automatically_derived]
+            impl #impl_generics #builder_name < #( #ty_generics ),* > #where_clause {
+                #deprecated
+                #doc
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #method_name #method_generics (self, #(#param_list),*) -> #return_type {
+                    let #field_name = (#arg_expr,);
+                    let ( #(#descructuring,)* ) = self.fields;
+                    #new_builder
+                }
+                #fallback_setter
+                #maybe_setter
+            }
+            #conflicting_setters
+        };
+        if overridable {
+            return Ok(setter);
+        }
+
+        let repeated_maybe_setter = field.builder_attr.setter.maybe.map(|_| {
+            quote! {
+                #[deprecated(
+                    note = #repeated_fields_error_message
+                )]
+                pub fn #maybe_method_name (self, _: #repeated_fields_error_type_name) -> #builder_name <#( #target_generics ),*> {
+                    self
+                }
+            }
+        });
+        // After a `maybe_` setter the field is in another state, which needs its own overloads.
+        let repeated_after_maybe = field.builder_attr.setter.maybe.map(|_| {
+            let fallback_setter = field.builder_attr.setter.strip_option_fallback.as_ref().map(|fallback_name| {
+                quote! {
+                    #[deprecated(
+                        note = #repeated_fields_error_message
+                    )]
+                    pub fn #fallback_name (self, _: #repeated_fields_error_type_name) -> #builder_name <#( #maybe_generics ),*> {
+                        self
+                    }
+                }
+            });
+            quote! {
+                #[doc(hidden)]
+                #[allow(dead_code, non_camel_case_types, missing_docs)]
+                #[automatically_derived]
+                impl #impl_generics #builder_name < #( #maybe_generics ),* > #where_clause {
+                    #[deprecated(
+                        note = #repeated_fields_error_message
+                    )]
+                    pub fn #method_name (self, _: #repeated_fields_error_type_name) -> #builder_name <#( #maybe_generics ),*> {
+                        self
+                    }
+                    #[deprecated(
+                        note = #repeated_fields_error_message
+                    )]
+                    pub fn #maybe_method_name (self, _: #repeated_fields_error_type_name) -> #builder_name <#( #maybe_generics ),*> {
+                        self
+                    }
+                    #fallback_setter
+                }
+            }
+        });
+        let repeated_fallback_setter = field.builder_attr.setter.strip_option_fallback.as_ref().map(|fallback_name| {
+            quote! {
+                #[deprecated(
+                    note = #repeated_fields_error_message
+                )]
+                pub fn #fallback_name (self, _: #repeated_fields_error_type_name) -> #builder_name <#( #target_generics ),*> {
+                    self
+                }
+            }
+        });
+
+        Ok(quote! {
+            #setter
+            #[doc(hidden)]
+            #[allow(dead_code, non_camel_case_types, non_snake_case)]
+            #[allow(clippy::exhaustive_enums)]
+            pub enum #repeated_fields_error_type_name {}
+            #[doc(hidden)]
+            #[allow(dead_code, non_camel_case_types, missing_docs)]
+            #[automatically_derived]
+            impl #impl_generics #builder_name < #( #target_generics ),* > #where_clause {
+                #[deprecated(
+                    note = #repeated_fields_error_message
+                )]
+                pub fn #method_name (self, _: #repeated_fields_error_type_name) -> #builder_name <#( #target_generics ),*> {
+                    self
+                }
+                #repeated_fallback_setter
+                #repeated_maybe_setter
+            }
+            #repeated_after_maybe
+        })
+    }
+
+    /// The parameters of a field's setter, and the expression that turns them into the field's
+    /// value - before `validate` and `strip_option` are applied.
+    fn setter_params(&self, field: &FieldInfo) -> Result<(Vec<(TokenStream, TokenStream)>, TokenStream), Error> {
+        let FieldInfo {
+            name: ref field_name,
+            ty: field_type,
+            ..
+        } = *field;
+
+        // NOTE: both auto_into and strip_option affect `arg_type` and `arg_expr`, but the order of
+        // nesting is different so we have to do this little dance.
+        let arg_type = if field.builder_attr.setter.strip_option.is_some() && field.builder_attr.setter.transform.is_none() {
+            field
+                .type_from_inside_option()
+                .ok_or_else(|| Error::new_spanned(field_type, "can't `strip_option` - field is not `Option<...>`"))?
+        } else {
+            field_type
+        };
+        let (arg_type, arg_expr) = if field.builder_attr.setter.auto_into.is_some() {
+            (quote!(impl ::core::convert::Into<#arg_type>), quote!(#field_name.into()))
+        } else {
+            (arg_type.to_token_stream(), field_name.to_token_stream())
+        };
+
+        Ok(if field.builder_attr.setter.strip_bool.is_some() {
+            (Vec::new(), quote!(true))
+        } else if field.builder_attr.setter.nested.is_some() {
+            let crate_module_path = &self.builder_attr.crate_module_path;
+            (
+                vec![(
+                    field_name.to_token_stream(),
+                    quote!(impl ::core::ops::FnOnce(<#field_type as #crate_module_path::HasTypedBuilder>::Builder) -> __NestedBuilder),
+                )],
+                quote!(#crate_module_path::FinishBuild::finish_build(#field_name(
+                    <#field_type as #crate_module_path::HasTypedBuilder>::create_builder()
+                ))),
+            )
+        } else if let Some(transform) = &field.builder_attr.setter.transform {
+            let params = transform
+                .params
+                .iter()
+                .map(|(pat, ty)| (pat.to_token_stream(), ty.to_token_stream()));
+            let body = &transform.body;
+            (params.collect(), quote!({ #body }))
+        } else if let Some(transform) = &field.builder_attr.setter.try_transform {
+            let params = transform
+                .params
+                .iter()
+                .map(|(pat, ty)| (pat.to_token_stream(), ty.to_token_stream()));
+            let body = &transform.body;
+            let output = &transform.output;
+            (
+                params.collect(),
+                quote!({
+                    let __value: #output = #body;
+                    __value?
+                }),
+            )
+        } else {
+            (vec![(field_name.to_token_stream(), arg_type)], arg_expr)
+        })
+    }
+
+    /// The parameters of a field's setter, and the expression that turns them into the value stored
+    /// for the field - with `validate` and `strip_option` applied.
+    fn setter_value(&self, field: &FieldInfo) -> Result<(Vec<(TokenStream, TokenStream)>, TokenStream), Error> {
+        let (params, arg_expr) = self.setter_params(field)?;
+        let arg_expr = if let Some(validate) = &field.builder_attr.setter.validate {
+            let crate_module_path = &self.builder_attr.crate_module_path;
+            let field_name_str = strip_raw_ident_prefix(field.name.to_string());
+            quote!({
+                let __value = #arg_expr;
+                #[allow(clippy::redundant_closure_call)]
+                if !(#validate)(&__value) {
+                    return ::core::result::Result::Err(#crate_module_path::ValidationError { field: #field_name_str });
+                }
+                __value
+            })
+        } else {
+            arg_expr
+        };
+        let arg_expr = if field.builder_attr.setter.strip_option.is_some() && field.builder_attr.setter.transform.is_none() {
+            quote!(Some(#arg_expr))
+        } else {
+            arg_expr
+        };
+        Ok((params, arg_expr))
+    }
+
+    /// The error type of the setter, if it is fallible.
+    fn setter_error_type(&self, field: &FieldInfo) -> Option<TokenStream> {
+        if field.builder_attr.setter.validate.is_some() {
+            let crate_module_path = &self.builder_attr.crate_module_path;
+            Some(quote!(#crate_module_path::ValidationError))
+        } else {
+            let transform = field.builder_attr.setter.try_transform.as_ref()?;
+            if let Some(error) = transform.try_error_type() {
+                Some(error.to_token_stream())
+            } else {
+                // An alias like `io::Result<T>`, whose error type is only known through the alias.
+                let crate_module_path = &self.builder_attr.crate_module_path;
+                let output = &transform.output;
+                Some(quote!(<#output as #crate_module_path::TransformResult>::Error))
+            }
+        }
+    }
+
+    /// The type of the parameter of the `maybe_` setter - an `Option` of what the regular setter
+    /// accepts (without `into`).
+    fn maybe_setter_param_type(&self, field: &FieldInfo) -> Result<TokenStream, Error> {
+        let field_type = field.ty;
+        let arg_type = if field.builder_attr.setter.strip_option.is_some() {
+            field
+                .type_from_inside_option()
+                .ok_or_else(|| Error::new_spanned(field_type, "can't `strip_option` - field is not `Option<...>`"))?
+        } else {
+            field_type
+        };
+        Ok(quote!(::core::option::Option<#arg_type>))
+    }
+
+    fn setter_method_generics(&self, field: &FieldInfo) -> TokenStream {
+        if field.builder_attr.setter.nested.is_some() {
+            let crate_module_path = &self.builder_attr.crate_module_path;
+            let field_type = field.ty;
+            quote!(<__NestedBuilder: #crate_module_path::FinishBuild<#field_type>>)
+        } else {
+            quote!()
+        }
+    }
+
+    /// The parameters of a field's `each` setter, and the expression that turns them into an item.
+    fn each_setter_params(&self, field: &FieldInfo, each: &EachSettings) -> (Vec<(TokenStream, TokenStream)>, TokenStream) {
+        let field_type = field.ty;
+        let wrap_type = |ty: TokenStream| {
+            if each.auto_into.is_some() {
+                quote!(impl ::core::convert::Into<#ty>)
+            } else {
+                ty
+            }
+        };
+        let wrap_expr = |expr: TokenStream| {
+            if each.auto_into.is_some() {
+                quote!(#expr.into())
+            } else {
+                expr
+            }
+        };
+        match field.type_generic_args().as_slice() {
+            [key_type, value_type, ..] if each.key_value.is_some() => {
+                let key_expr = wrap_expr(quote!(key));
+                let value_expr = wrap_expr(quote!(value));
+                (
+                    vec![
+                        (quote!(key), wrap_type(key_type.to_token_stream())),
+                        (quote!(value), wrap_type(value_type.to_token_stream())),
+                    ],
+                    quote!((#key_expr, #value_expr)),
+                )
+            }
+            // The item type is taken from the collection rather than from its type arguments, which
+            // aren't always the item type - e.g. `SmallVec<[T; 4]>`.
+            _ => (
+                vec![(
+                    quote!(item),
+                    wrap_type(quote!(<#field_type as ::core::iter::IntoIterator>::Item)),
+                )],
+                wrap_expr(quote!(item)),
+            ),
+        }
+    }
+
+    fn each_setter_impl(&self, field: &FieldInfo, each: &EachSettings) -> Result<TokenStream, Error> {
+        if let Some(dependency) = self.default_dependencies(field).first() {
+            let mut error = Error::new(
+                each.name.span(),
+                format_args!("each conflicts with a default that refers to {}", dependency.name),
+            );
+            error.combine(Error::new_spanned(&dependency.name, "field declared here"));
+            return Err(error);
+        }
+        let StructInfo { ref builder_name, .. } = *self;
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let field_name = &field.name;
+        let field_type = field.ty;
+        let field_generic = &field.generic_ident;
+
+        let descructuring = self.included_fields().map(|f| &f.name).collect::<Vec<_>>();
+        let reconstructing = self.included_fields().map(|f| &f.name);
+
+        let mut generics = self.generics.clone();
+        let mut source_state = Vec::new();
+        let mut target_state = Vec::new();
+        for f in self.included_fields() {
+            if self.is_exclusive_with(field, f) {
+                source_state.push(empty_type());
+                target_state.push(empty_type());
+                continue;
+            }
+            generics.params.push(f.generic_ty_param());
+            source_state.push(f.type_ident());
+            if f.ordinal == field.ordinal {
+                target_state.push(f.tuplized_type_ty_param());
+            } else {
+                target_state.push(f.type_ident());
+            }
+        }
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(#field_generic: #crate_module_path::Optional<#field_type>));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        let generic_args = self.generic_args();
+        let generic_args = quote!(#(#generic_args,)*);
+
+        let (params, item_expr) = self.each_setter_params(field, each);
+        let param_list = params.iter().map(|(pat, ty)| quote!(#pat: #ty));
+
+        let deprecated = &field.builder_attr.deprecated;
+        let method_name = &each.name;
+        let default = &field.builder_attr.default;
+
+        Ok(quote! {
+            #[allow(dead_code, non_camel_case_types, missing_docs)]
+            #[automatically_derived]
+            impl #impl_generics #builder_name < #generic_args (#(#source_state,)*) > #where_clause {
+                #deprecated
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #method_name (self, #(#param_list),*) -> #builder_name < #generic_args (#(#target_state,)*) > {
+                    let __item = #item_expr;
+                    let ( #(#descructuring,)* ) = self.fields;
+                    let mut #field_name = #crate_module_path::Optional::into_value(#field_name, || #default);
+                    ::core::iter::Extend::extend(&mut #field_name, ::core::iter::once(__item));
+                    let #field_name = (#field_name,);
+                    #builder_name {
+                        fields: ( #(#reconstructing,)* ),
+                        phantom: self.phantom,
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn to_builder_methods_impl(&self) -> Result<TokenStream, Error> {
+        let Some(into_builder_span) = self.builder_attr.into_builder else {
+            return Ok(quote!());
+        };
+        if self.variant.is_some() {
+            return Err(Error::new(into_builder_span, "into_builder is not supported on enums"));
+        }
+        if let Some(flatten) = self.included_fields().find_map(|f| f.builder_attr.flatten) {
+            let mut error = Error::new(into_builder_span, "into_builder is not supported with flatten fields");
+            error.combine(Error::new(flatten, "flatten set here"));
+            return Err(error);
+        }
+        let StructInfo {
+            ref name,
+            ref builder_name,
+            ..
+        } = *self;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let generic_args = self.generic_args();
+        let all_set_state = self.included_fields().map(|f| f.tuplized_type_ty_param());
+        let destructuring = if self.is_tuple {
+            let field_bindings = self.fields.iter().map(|f| {
+                if f.builder_attr.setter.skip.is_some() {
+                    quote!(_)
+                } else {
+                    f.name.to_token_stream()
+                }
+            });
+            quote!(#name ( #(#field_bindings),* ))
+        } else {
+            let field_bindings = self.fields.iter().map(|f| {
+                let field_name = &f.name;
+                if f.builder_attr.setter.skip.is_some() {
+                    quote!(#field_name: _)
+                } else {
+                    field_name.to_token_stream()
+                }
+            });
+            quote!(#name { #(#field_bindings),* })
+        };
+        let field_names = self.included_fields().map(|f| &f.name);
+        let builder_type = quote!(#builder_name < #(#generic_args,)* (#(#all_set_state,)*) >);
+        let visibility = first_visibility(&[
+            self.builder_attr.builder_method.vis.as_ref(),
+            self.builder_attr.builder_type.vis.as_ref(),
+            Some(self.vis),
+        ]);
+
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// Create a builder with all the fields set to the values of this instance.
+                #[allow(dead_code, clippy::used_underscore_binding)]
+                #visibility fn into_builder(self) -> #builder_type {
+                    let #destructuring = self;
+                    #builder_name {
+                        fields: ( #((#field_names,),)* ),
+                        phantom: ::core::default::Default::default(),
+                    }
+                }
+
+                /// Create a builder with all the fields set to clones of the values of this instance.
+                #[allow(dead_code)]
+                #visibility fn to_builder(&self) -> #builder_type
+                where
+                    for<'__typed_builder> #name #ty_generics: ::core::clone::Clone,
+                {
+                    ::core::clone::Clone::clone(self).into_builder()
+                }
+            }
+        })
+    }
+
+    /// The name of the runtime-checked builder generated with `#[builder(dynamic)]`.
+    fn dyn_builder_name(&self) -> Ident {
+        let name = if let Some(variant) = self.variant {
+            format!("{}{}DynBuilder", self.name, variant)
+        } else {
+            format!("{}DynBuilder", self.name)
+        };
+        Ident::new(&strip_raw_ident_prefix(name), Span::call_site())
+    }
+
+    pub fn dyn_builder_impl(&self) -> Result<TokenStream, Error> {
+        let Some(dynamic_span) = self.builder_attr.dynamic else {
+            if let Some(from_str) = self.included_fields().find_map(|f| f.builder_attr.setter.from_str) {
+                return Err(Error::new(from_str, "from_str requires #[builder(dynamic)] on the type"));
+            }
+            return Ok(quote!());
+        };
+        if let Some(flatten) = self.included_fields().find_map(|f| f.builder_attr.flatten) {
+            let mut error = Error::new(dynamic_span, "dynamic is not supported with flatten fields");
+            error.combine(Error::new(flatten, "flatten set here"));
+            return Err(error);
+        }
+        let required_fields = self
+            .included_fields()
+            .filter(|f| f.builder_attr.default.is_none())
+            .collect::<Vec<_>>();
+        if required_fields.len() > 64 {
+            return Err(Error::new(
+                dynamic_span,
+                "dynamic is not supported with more than 64 required fields",
+            ));
+        }
+
+        let StructInfo {
+            vis, ref builder_name, ..
+        } = *self;
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let dyn_builder_name = self.dyn_builder_name();
+        let generics = self.generics;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let visibility = first_visibility(&[self.builder_attr.builder_type.vis.as_ref(), Some(vis)]);
+        let doc = format!(
+            "Runtime-checked builder for [`{target}`] instances.\n\n\
+            Unlike the typed builder, its setters take `&mut self`, so it can be filled in loops or from \
+            data, and the build method checks at runtime that all the required fields were set.",
+            target = self.target_name(),
+        );
+
+        let field_types = self.included_fields().map(|f| f.ty);
+        let empties = self.included_fields().map(|_| quote!(::core::option::Option::None));
+        let phantom_generics = self.phantom_generics();
+
+        let setters = self
+            .included_fields()
+            .enumerate()
+            .map(|(index, field)| self.dyn_setters_impl(syn::Index::from(index), field))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let build_method_name = self.build_method_name();
+        let build_method_visibility = self.build_method_visibility();
+        let (build_method_generic, output_type, build_method_where_predicates) = self.build_method_signature();
+        let build_method_where_clause = if build_method_where_predicates.is_empty() {
+            None
+        } else {
+            Some(quote!(where #( #build_method_where_predicates ),*))
+        };
+        let field_names = self.included_fields().map(|f| &f.name).collect::<Vec<_>>();
+        let states = self.included_fields().map(|f| {
+            let name = &f.name;
+            if f.builder_attr.default.is_some() {
+                quote!(#crate_module_path::Maybe(#name))
+            } else {
+                quote!((#name,))
+            }
+        });
+        let typed_builder = quote! {
+            #builder_name {
+                fields: ( #(#states,)* ),
+                phantom: ::core::marker::PhantomData,
+            }
+        };
+        let build_body = if required_fields.is_empty() {
+            quote! {
+                let ( #(#field_names,)* ) = self.fields;
+                ::core::result::Result::Ok(#typed_builder.#build_method_name())
+            }
+        } else {
+            let required_names = required_fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+            let required_name_strs = required_fields.iter().map(|f| strip_raw_ident_prefix(f.name.to_string()));
+            let missing_checks = required_names.iter().enumerate().map(|(bit, name)| {
+                quote! {
+                    if #name.is_none() {
+                        __missing |= 1 << #bit;
+                    }
+                }
+            });
+            quote! {
+                let ( #(#field_names,)* ) = self.fields;
+                let mut __missing = 0u64;
+                #( #missing_checks )*
+                match ( #(#required_names,)* ) {
+                    ( #(::core::option::Option::Some(#required_names),)* ) => {
+                        ::core::result::Result::Ok(#typed_builder.#build_method_name())
+                    }
+                    _ => ::core::result::Result::Err(#crate_module_path::MissingFieldsError::new(
+                        &[ #(#required_name_strs),* ],
+                        __missing,
+                    )),
+                }
+            }
+        };
+
+        let conversions = self.dyn_conversions_impl();
+        let set_by_name = self.set_by_name_impl()?;
+
+        Ok(quote! {
+            #conversions
+
+            #[doc = #doc]
+            #[allow(dead_code, non_camel_case_types, non_snake_case)]
+            #visibility struct #dyn_builder_name #generics #where_clause {
+                fields: ( #( ::core::option::Option<#field_types>, )* ),
+                phantom: ::core::marker::PhantomData<(#( #phantom_generics ),*)>,
+            }
+
+            #[automatically_derived]
+            impl #impl_generics ::core::default::Default for #dyn_builder_name #ty_generics #where_clause {
+                fn default() -> Self {
+                    Self {
+                        fields: ( #(#empties,)* ),
+                        phantom: ::core::marker::PhantomData,
+                    }
+                }
+            }
+
+            #[allow(dead_code, non_camel_case_types, missing_docs)]
+            #[automatically_derived]
+            impl #impl_generics #dyn_builder_name #ty_generics #where_clause {
+                /// Create a builder with none of the fields set.
+                pub fn new() -> Self {
+                    ::core::default::Default::default()
+                }
+
+                #( #setters )*
+
+                #set_by_name
+
+                /// Finalise the builder, failing if any of the required fields was not set.
+                #[allow(clippy::default_trait_access, clippy::used_underscore_binding)]
+                #build_method_visibility fn #build_method_name #build_method_generic (self)
+                    -> ::core::result::Result<#output_type, #crate_module_path::MissingFieldsError>
+                    #build_method_where_clause
+                {
+                    #build_body
+                }
+            }
+        })
+    }
+
+    /// The `set_by_name` method of the runtime-checked builder, which sets the `from_str` fields
+    /// from strings.
+    fn set_by_name_impl(&self) -> Result<TokenStream, Error> {
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let mut arms = Vec::new();
+        for (index, field) in self.included_fields().enumerate() {
+            if field.builder_attr.setter.from_str.is_none() {
+                continue;
+            }
+            let index = syn::Index::from(index);
+            let key = strip_raw_ident_prefix(field.setter_method_name().to_string());
+            let field_name = &field.name;
+            let field_name_str = strip_raw_ident_prefix(field_name.to_string());
+            let field_type = field.ty;
+            let parsed_type = if field.builder_attr.setter.strip_option.is_some() {
+                field
+                    .type_from_inside_option()
+                    .ok_or_else(|| Error::new_spanned(field_type, "can't `strip_option` - field is not `Option<...>`"))?
+            } else {
+                field_type
+            };
+            let invalid_value = quote!(#crate_module_path::SetByNameError::InvalidValue { field: #field_name_str });
+            let validation = field.builder_attr.setter.validate.as_ref().map(|validate| {
+                quote! {
+                    #[allow(clippy::redundant_closure_call)]
+                    if !(#validate)(&#field_name) {
+                        return ::core::result::Result::Err(#invalid_value);
+                    }
+                }
+            });
+            let value = if field.builder_attr.setter.strip_option.is_some() {
+                quote!(::core::option::Option::Some(#field_name))
+            } else {
+                field_name.to_token_stream()
+            };
+            arms.push(quote! {
+                #key => {
+                    let ::core::result::Result::Ok(#field_name) = <#parsed_type as ::core::str::FromStr>::from_str(value) else {
+                        return ::core::result::Result::Err(#invalid_value);
+                    };
+                    #validation
+                    self.fields.#index = ::core::option::Option::Some(#value);
+                }
+            });
+        }
+        if arms.is_empty() {
+            return Ok(quote!());
+        }
+        Ok(quote! {
+            /// Set the field whose setter is named `name` by parsing `value` - for the fields marked
+            /// with `setter(from_str)`.
+            #[allow(clippy::used_underscore_binding)]
+            pub fn set_by_name<'__name>(
+                &mut self,
+                name: &'__name str,
+                value: &str,
+            ) -> ::core::result::Result<&mut Self, #crate_module_path::SetByNameError<'__name>> {
+                match name {
+                    #( #arms )*
+                    _ => return ::core::result::Result::Err(#crate_module_path::SetByNameError::UnknownField(name)),
+                }
+                ::core::result::Result::Ok(self)
+            }
+        })
+    }
+
+    /// The `into_dyn` method of the typed builder, and the `try_into_typed` method of the
+    /// runtime-checked builder that converts it back.
+    fn dyn_conversions_impl(&self) -> TokenStream {
+        let StructInfo {
+            vis, ref builder_name, ..
+        } = *self;
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let dyn_builder_name = self.dyn_builder_name();
+        let state_trait_name = syn::Ident::new(&format!("{}_State", dyn_builder_name), Span::call_site());
+        let generics = self.generics;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let visibility = first_visibility(&[self.builder_attr.builder_type.vis.as_ref(), Some(vis)]);
+        let generic_args = self.generic_args();
+
+        let field_names = self.included_fields().map(|f| &f.name).collect::<Vec<_>>();
+        let options_type = {
+            let field_types = self.included_fields().map(|f| f.ty);
+            quote!(( #( ::core::option::Option<#field_types>, )* ))
+        };
+        let state_generics = {
+            let mut generics = self.generics.clone();
+            for field in self.included_fields() {
+                let field_type = field.ty;
+                let mut generic_param: syn::TypeParam = field.generic_ident.clone().into();
+                generic_param
+                    .bounds
+                    .push(syn::parse_quote!(#crate_module_path::FieldState<#field_type>));
+                generics.params.push(generic_param.into());
+            }
+            generics
+        };
+        let (state_impl_generics, _, state_where_clause) = state_generics.split_for_impl();
+        let states = self.included_fields().map(|f| f.type_ident()).collect::<Vec<_>>();
+
+        quote! {
+            #[automatically_derived]
+            impl #state_impl_generics #builder_name < #(#generic_args,)* (#(#states,)*) > #state_where_clause {
+                /// Convert the builder into a runtime-checked builder, keeping the values of the
+                /// fields that were already set.
+                #[allow(clippy::used_underscore_binding)]
+                pub fn into_dyn(self) -> #dyn_builder_name #ty_generics {
+                    let ( #(#field_names,)* ) = self.fields;
+                    #dyn_builder_name {
+                        fields: ( #( #crate_module_path::FieldState::into_option(#field_names), )* ),
+                        phantom: ::core::marker::PhantomData,
+                    }
+                }
+            }
+
+            #[doc(hidden)]
+            #[allow(dead_code, non_camel_case_types, missing_docs)]
+            #visibility trait #state_trait_name #generics #where_clause: ::core::marker::Sized {
+                fn try_from_options(fields: #options_type) -> ::core::result::Result<Self, #options_type>;
+            }
+
+            #[automatically_derived]
+            impl #state_impl_generics #state_trait_name #ty_generics for (#(#states,)*) #state_where_clause {
+                #[allow(clippy::used_underscore_binding)]
+                fn try_from_options(fields: #options_type) -> ::core::result::Result<Self, #options_type> {
+                    let ( #(#field_names,)* ) = fields;
+                    match ( #( #crate_module_path::FieldState::try_from_option(#field_names), )* ) {
+                        ( #( ::core::result::Result::Ok(#field_names), )* ) => ::core::result::Result::Ok(( #(#field_names,)* )),
+                        ( #(#field_names,)* ) => ::core::result::Result::Err(( #(
+                            match #field_names {
+                                ::core::result::Result::Ok(state) => #crate_module_path::FieldState::into_option(state),
+                                ::core::result::Result::Err(value) => value,
+                            },
+                        )* )),
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_generics #dyn_builder_name #ty_generics #where_clause {
+                /// Convert the builder into a typed builder in the state `__S`, which must match which
+                /// fields are set. Returns the builder unchanged if it does not.
+                pub fn try_into_typed<__S: #state_trait_name #ty_generics>(
+                    self,
+                ) -> ::core::result::Result<#builder_name < #(#generic_args,)* __S >, Self> {
+                    match __S::try_from_options(self.fields) {
+                        ::core::result::Result::Ok(fields) => ::core::result::Result::Ok(#builder_name {
+                            fields,
+                            phantom: ::core::marker::PhantomData,
+                        }),
+                        ::core::result::Result::Err(fields) => ::core::result::Result::Err(Self {
+                            fields,
+                            phantom: ::core::marker::PhantomData,
+                        }),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The `with_env` method of the builder, which reads the `env` fields from environment
+    /// variables.
+    pub fn with_env_impl(&self) -> Result<TokenStream, Error> {
+        let env_fields = self
+            .included_fields()
+            .filter(|f| f.builder_attr.env.is_some())
+            .collect::<Vec<_>>();
+        let Some(first_env) = env_fields.first().and_then(|f| f.builder_attr.env.as_ref()) else {
+            return Ok(quote!());
+        };
+        if !cfg!(feature = "std") {
+            return Err(Error::new_spanned(
+                first_env,
+                "env requires the `std` feature of typed-builder",
+            ));
+        }
+
+        let StructInfo { ref builder_name, .. } = *self;
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let mut generics = self.generics.clone();
+        for f in self.included_fields() {
+            if f.builder_attr.env.is_none() {
+                generics.params.push(f.generic_ty_param());
+            }
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let generic_args = self.generic_args();
+        let source_state = self.included_fields().map(|f| {
+            if f.builder_attr.env.is_some() {
+                empty_type()
+            } else {
+                f.type_ident()
+            }
+        });
+        let target_state = self.included_fields().map(|f| {
+            if f.builder_attr.env.is_some() {
+                f.maybe_state_type(crate_module_path)
+            } else {
+                f.type_ident()
+            }
+        });
+        let field_names = self.included_fields().map(|f| &f.name).collect::<Vec<_>>();
+        let destructuring = self.included_fields().map(|f| {
+            if f.builder_attr.env.is_some() {
+                quote!(_)
+            } else {
+                f.name.to_token_stream()
+            }
+        });
+
+        let reads = env_fields
+            .iter()
+            .map(|field| {
+                let field_name = &field.name;
+                let field_type = field.ty;
+                let variable = field.builder_attr.env.as_ref();
+                let parsed_type = if field.builder_attr.setter.strip_option.is_some() {
+                    field
+                        .type_from_inside_option()
+                        .ok_or_else(|| Error::new_spanned(field_type, "can't `strip_option` - field is not `Option<...>`"))?
+                } else {
+                    field_type
+                };
+                let validate = if let Some(validate) = &field.builder_attr.setter.validate {
+                    quote!(#validate)
+                } else {
+                    quote!(|_: &#parsed_type| true)
+                };
+                let value = if field.builder_attr.setter.strip_option.is_some() {
+                    quote!(#field_name.map(::core::option::Option::Some))
+                } else {
+                    field_name.to_token_stream()
+                };
+                Ok(quote! {
+                    #[allow(clippy::redundant_closure_call)]
+                    let #field_name = #crate_module_path::Maybe({
+                        let #field_name: ::core::option::Option<#parsed_type> = __reader.read(#variable, #validate);
+                        #value
+                    });
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(quote! {
+            #[allow(dead_code, non_camel_case_types, missing_docs)]
+            #[automatically_derived]
+            impl #impl_generics #builder_name < #(#generic_args,)* (#(#source_state,)*) > #where_clause {
+                /// Set the fields marked with `env` from their environment variables. Fields whose
+                /// variable is not set use their default. Fails if any of the variables cannot be
+                /// parsed.
+                #[allow(clippy::used_underscore_binding)]
+                pub fn with_env(
+                    self,
+                ) -> ::core::result::Result<#builder_name < #(#generic_args,)* (#(#target_state,)*) >, #crate_module_path::EnvError> {
+                    let ( #(#destructuring,)* ) = self.fields;
+                    let mut __reader = #crate_module_path::EnvReader::default();
+                    #( #reads )*
+                    __reader.finish()?;
+                    ::core::result::Result::Ok(#builder_name {
+                        fields: ( #(#field_names,)* ),
+                        phantom: self.phantom,
+                    })
+                }
+            }
+        })
+    }
+
+    pub fn deserialize_impl(&self) -> Result<TokenStream, Error> {
+        let Some(deserialize_span) = self.builder_attr.deserialize else {
+            return Ok(quote!());
+        };
+        if !cfg!(feature = "serde") {
+            return Err(Error::new(
+                deserialize_span,
+                "deserialize requires the `serde` feature of typed-builder",
+            ));
+        }
+        if let Some(flatten) = self.included_fields().find_map(|f| f.builder_attr.flatten) {
+            let mut error = Error::new(deserialize_span, "deserialize is not supported with flatten fields");
+            error.combine(Error::new(flatten, "flatten set here"));
+            return Err(error);
+        }
+
+        let StructInfo {
+            vis, ref builder_name, ..
+        } = *self;
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let serde = quote!(#crate_module_path::__serde);
+        let visibility = first_visibility(&[self.builder_attr.builder_type.vis.as_ref(), Some(vis)]);
+        let partial_name = syn::Ident::new(
+            &strip_raw_ident_prefix(if let Some(variant) = self.variant {
+                format!("{}{}Partial", self.name, variant)
+            } else {
+                format!("{}Partial", self.name)
+            }),
+            Span::call_site(),
+        );
+        let generic_args = self.generic_args();
+        let required_fields = self
+            .included_fields()
+            .filter(|f| f.builder_attr.default.is_none())
+            .collect::<Vec<_>>();
+        // The alias takes the states of the fields without a default after the struct's own generics,
+        // so that it can name the builder with them either read from the data or left for their
+        // setters - the default.
+        let alias_params = self
+            .generics
+            .params
+            .iter()
+            .map(|param| match param {
+                syn::GenericParam::Type(type_param) => type_param.ident.to_token_stream(),
+                syn::GenericParam::Lifetime(lifetime_def) => lifetime_def.lifetime.to_token_stream(),
+                syn::GenericParam::Const(const_param) => {
+                    let ident = &const_param.ident;
+                    let ty = &const_param.ty;
+                    quote!(const #ident: #ty)
+                }
+            })
+            .chain(required_fields.iter().map(|f| {
+                let generic_ident = &f.generic_ident;
+                quote!(#generic_ident = ())
+            }));
+        let partial_state = self.included_fields().map(|f| {
+            if f.builder_attr.default.is_some() {
+                f.maybe_state_type(crate_module_path)
+            } else {
+                f.type_ident()
+            }
+        });
+        let partial_type = quote!(#builder_name < #(#generic_args,)* (#(#partial_state,)*) >);
+
+        let wire_fields = self.included_fields().collect::<Vec<_>>();
+        let variants = wire_fields.iter().map(|f| &f.generic_ident).collect::<Vec<_>>();
+        let wire_names = wire_fields.iter().map(|f| f.serde_name()).collect::<Vec<_>>();
+        let wire_patterns = wire_fields
+            .iter()
+            .map(|f| {
+                let names = std::iter::once(f.serde_name()).chain(f.serde_aliases.iter().cloned());
+                quote!(#(#names)|*)
+            })
+            .collect::<Vec<_>>();
+        let wire_types = wire_fields.iter().map(|f| f.ty).collect::<Vec<_>>();
+        // The fields without a default are converted to the state the partial's type gives them,
+        // failing if the data doesn't fit it rather than silently dropping their values.
+        let field_states = self.included_fields().map(|f| {
+            let generic_ident = &f.generic_ident;
+            if f.builder_attr.default.is_some() {
+                return quote!(#crate_module_path::Maybe(#generic_ident));
+            }
+            let field_type = f.ty;
+            let name = f.serde_name();
+            let unexpected = format!(
+                "field `{}` has no default, and the partial's type leaves it for its setter",
+                name
+            );
+            quote! {
+                match <#generic_ident as #crate_module_path::FieldState<#field_type>>::try_from_option(#generic_ident) {
+                    ::core::result::Result::Ok(state) => state,
+                    ::core::result::Result::Err(::core::option::Option::None) => {
+                        return ::core::result::Result::Err(<__A::Error as #serde::de::Error>::missing_field(#name));
+                    }
+                    ::core::result::Result::Err(::core::option::Option::Some(_)) => {
+                        return ::core::result::Result::Err(<__A::Error as #serde::de::Error>::custom(#unexpected));
+                    }
+                }
+            }
+        });
+
+        let mut generics = self.generics.clone();
+        generics.params.insert(0, syn::parse_quote!('__de));
+        for field in &required_fields {
+            let field_type = field.ty;
+            let mut generic_param: syn::TypeParam = field.generic_ident.clone().into();
+            generic_param
+                .bounds
+                .push(syn::parse_quote!(#crate_module_path::FieldState<#field_type>));
+            generics.params.push(generic_param.into());
+        }
+        {
+            let predicates = &mut generics.make_where_clause().predicates;
+            for ty in &wire_types {
+                predicates.push(syn::parse_quote!(#ty: #serde::Deserialize<'__de>));
+            }
+        }
+        let (impl_generics, visitor_ty_generics, where_clause) = generics.split_for_impl();
+        let expecting = format!("a partial {}", self.target_name());
+        let doc = format!(
+            "A builder for [`{}`] deserialized from data, holding the fields with a default that the data \
+            contains, and the fields without one whose state is given as `(T,)` - which the data must contain.",
+            self.target_name()
+        );
+
+        Ok(quote! {
+            #[doc = #doc]
+            #[allow(dead_code)]
+            #visibility type #partial_name < #(#alias_params),* > = #partial_type;
+
+            #[allow(non_camel_case_types, non_snake_case, clippy::used_underscore_binding)]
+            const _: () = {
+                enum __Field {
+                    #( #variants, )*
+                    __ignore,
+                }
+
+                struct __FieldVisitor;
+
+                impl<'__de> #serde::de::Visitor<'__de> for __FieldVisitor {
+                    type Value = __Field;
+
+                    fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                        formatter.write_str("field identifier")
+                    }
+
+                    fn visit_str<__E: #serde::de::Error>(self, value: &str) -> ::core::result::Result<__Field, __E> {
+                        ::core::result::Result::Ok(match value {
+                            #( #wire_patterns => __Field::#variants, )*
+                            _ => __Field::__ignore,
+                        })
+                    }
+                }
+
+                impl<'__de> #serde::Deserialize<'__de> for __Field {
+                    fn deserialize<__D: #serde::Deserializer<'__de>>(deserializer: __D) -> ::core::result::Result<Self, __D::Error> {
+                        deserializer.deserialize_identifier(__FieldVisitor)
+                    }
+                }
+
+                struct __Visitor #impl_generics #where_clause {
+                    phantom: ::core::marker::PhantomData<(&'__de (), fn() -> #partial_type)>,
+                }
+
+                impl #impl_generics #serde::de::Visitor<'__de> for __Visitor #visitor_ty_generics #where_clause {
+                    type Value = #partial_type;
+
+                    fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                        formatter.write_str(#expecting)
+                    }
+
+                    fn visit_map<__A: #serde::de::MapAccess<'__de>>(self, mut map: __A) -> ::core::result::Result<Self::Value, __A::Error> {
+                        #( let mut #variants: ::core::option::Option<#wire_types> = ::core::option::Option::None; )*
+                        while let ::core::option::Option::Some(key) = map.next_key::<__Field>()? {
+                            match key {
+                                #(
+                                    __Field::#variants => {
+                                        if #variants.is_some() {
+                                            return ::core::result::Result::Err(<__A::Error as #serde::de::Error>::duplicate_field(#wire_names));
+                                        }
+                                        #variants = ::core::option::Option::Some(map.next_value()?);
+                                    }
+                                )*
+                                __Field::__ignore => {
+                                    map.next_value::<#serde::de::IgnoredAny>()?;
+                                }
+                            }
+                        }
+                        ::core::result::Result::Ok(#builder_name {
+                            fields: ( #(#field_states,)* ),
+                            phantom: ::core::marker::PhantomData,
+                        })
+                    }
+                }
+
+                #[automatically_derived]
+                impl #impl_generics #serde::Deserialize<'__de> for #partial_type #where_clause {
+                    fn deserialize<__D: #serde::Deserializer<'__de>>(deserializer: __D) -> ::core::result::Result<Self, __D::Error> {
+                        deserializer.deserialize_map(__Visitor { phantom: ::core::marker::PhantomData })
+                    }
+                }
+            };
+        })
+    }
+
+    /// The setters of a field on the runtime-checked builder - the same ones the typed builder has,
+    /// but taking `&mut self`.
+    fn dyn_setters_impl(&self, index: syn::Index, field: &FieldInfo) -> Result<TokenStream, Error> {
+        let field_name = &field.name;
+        let doc = field.builder_attr.setter.doc.as_ref().map(|doc| quote!(#[doc = #doc]));
+        let deprecated = &field.builder_attr.deprecated;
+        let method_name = field.setter_method_name();
+
+        let each_setter = field.builder_attr.setter.each.as_ref().map(|each| {
+            let (params, item_expr) = self.each_setter_params(field, each);
+            let param_list = params.iter().map(|(pat, ty)| quote!(#pat: #ty));
+            let each_name = &each.name;
+            quote! {
+                #deprecated
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #each_name (&mut self, #(#param_list),*) -> &mut Self {
+                    let __item = #item_expr;
+                    let #field_name = self.fields.#index.get_or_insert_with(::core::default::Default::default);
+                    ::core::iter::Extend::extend(#field_name, ::core::iter::once(__item));
+                    self
+                }
+            }
+        });
+        let setter = if field
+            .builder_attr
+            .setter
+            .each
+            .as_ref()
+            .is_some_and(|each| each.name == method_name)
+        {
+            None
+        } else {
+            let (params, arg_expr) = self.setter_value(field)?;
+            let param_list = params.iter().map(|(pat, ty)| quote!(#pat: #ty));
+            let method_generics = self.setter_method_generics(field);
+            let (return_type, return_value) = if let Some(error) = self.setter_error_type(field) {
+                (
+                    quote!(::core::result::Result<&mut Self, #error>),
+                    quote!(::core::result::Result::Ok(self)),
+                )
+            } else {
+                (quote!(&mut Self), quote!(self))
+            };
+            Some(quote! {
+                #deprecated
+                #doc
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #method_name #method_generics (&mut self, #(#param_list),*) -> #return_type {
+                    let #field_name = #arg_expr;
+                    self.fields.#index = ::core::option::Option::Some(#field_name);
+                    #return_value
+                }
+            })
+        };
+        let fallback_setter = field.builder_attr.setter.strip_option_fallback.as_ref().map(|fallback_name| {
+            let field_type = field.ty;
+            quote! {
+                #deprecated
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #fallback_name (&mut self, #field_name: #field_type) -> &mut Self {
+                    self.fields.#index = ::core::option::Option::Some(#field_name);
+                    self
+                }
+            }
+        });
+        let maybe_setter = if field.builder_attr.setter.maybe.is_some() {
+            let maybe_method_name = field.maybe_method_name();
+            let param_type = self.maybe_setter_param_type(field)?;
+            let value = if field.builder_attr.setter.strip_option.is_some() {
+                quote!(#field_name.map(::core::option::Option::Some))
+            } else {
+                field_name.to_token_stream()
+            };
+            Some(quote! {
+                #deprecated
+                #[allow(clippy::used_underscore_binding)]
+                pub fn #maybe_method_name (&mut self, #field_name: #param_type) -> &mut Self {
+                    self.fields.#index = #value;
+                    self
+                }
+            })
+        } else {
+            None
+        };
+        let reset_setter = field.builder_attr.setter.reset.map(|_| {
+            let reset_method_name = field.reset_method_name();
+            quote! {
+                #deprecated
+                pub fn #reset_method_name (&mut self) -> &mut Self {
+                    self.fields.#index = ::core::option::Option::None;
+                    self
+                }
+            }
+        });
+        Ok(quote! {
+            #setter
+            #each_setter
+            #fallback_setter
+            #maybe_setter
+            #reset_setter
+        })
+    }
+
+    pub fn flatten_host_impl(&self, field: &FieldInfo) -> TokenStream {
+        let StructInfo { ref builder_name, .. } = *self;
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let field_name = &field.name;
+        let field_type = field.ty;
+        let field_generic = &field.generic_ident;
+
+        let mut generics = self.generics.clone();
+        for f in self.included_fields() {
+            generics.params.push(f.generic_ty_param());
+        }
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote_spanned!(field_type.span()=> #field_type: #crate_module_path::Flattenable));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let generic_args = self.generic_args();
+        let state = self.included_fields().map(|f| f.type_ident());
+        let replaced_state = self.included_fields().map(|f| {
+            if f.ordinal == field.ordinal {
+                quote!(__Replacement)
+            } else {
+                f.type_ident().to_token_stream()
+            }
+        });
+        let field_names = self.included_fields().map(|f| &f.name).collect::<Vec<_>>();
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #crate_module_path::FlattenHost<#field_type> for #builder_name < #(#generic_args,)* (#(#state,)*) > #where_clause {
+                type Inner = #field_generic;
+                type Replaced<__Replacement> = #builder_name < #(#generic_args,)* (#(#replaced_state,)*) >;
+
+                fn flatten_map<__Replacement>(
+                    self,
+                    __f: impl ::core::ops::FnOnce(Self::Inner) -> __Replacement,
+                ) -> Self::Replaced<__Replacement> {
+                    let ( #(#field_names,)* ) = self.fields;
+                    let #field_name = __f(#field_name);
+                    #builder_name {
+                        fields: ( #(#field_names,)* ),
+                        phantom: self.phantom,
+                    }
+                }
+
+                fn flatten_try_map<__Replacement, __Error>(
+                    self,
+                    __f: impl ::core::ops::FnOnce(Self::Inner) -> ::core::result::Result<__Replacement, __Error>,
+                ) -> ::core::result::Result<Self::Replaced<__Replacement>, __Error> {
+                    let ( #(#field_names,)* ) = self.fields;
+                    let #field_name = __f(#field_name)?;
+                    ::core::result::Result::Ok(#builder_name {
+                        fields: ( #(#field_names,)* ),
+                        phantom: self.phantom,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Generate the `<Builder>Setters` trait, which exposes the builder's setters on builders
+    /// that hold it in a `flatten` field.
+    ///
+    /// Each setter gets a hidden helper trait, implemented for the builder states the setter can
+    /// be called on, so that the type-state rules of the inner builder are kept.
+    pub fn flatten_setters_impl(&self) -> Result<TokenStream, Error> {
+        let Some(flattenable) = self.builder_attr.flattenable else {
+            return Ok(quote!());
+        };
+        if self.variant.is_some() {
+            return Err(Error::new(flattenable, "flattenable is not supported on enums"));
+        }
+        if !self.exposes_builder() {
+            return Err(Error::new(
+                flattenable,
+                "flattenable is not supported when the visibility of builder_method or builder_type is set",
+            ));
+        }
+        // Traits don't get the implied bounds structs get from their fields (like `T: 'a` for
+        // `&'a T`), so generating them for types with lifetimes may not compile.
+        if let Some(lifetime) = self.generics.lifetimes().next() {
+            let mut error = Error::new(flattenable, "flattenable is not supported on types with lifetime parameters");
+            error.combine(Error::new_spanned(lifetime, "lifetime parameter declared here"));
+            return Err(error);
+        }
+        let StructInfo {
+            vis,
+            ref name,
+            ref builder_name,
+            ..
+        } = *self;
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let (_, ty_generics, where_clause) = self.generics.split_for_impl();
+        let decl_generics = &self.generics.params;
+        let generic_args = self.generic_args();
+
+        let mut helpers = Vec::new();
+        let mut methods = Vec::new();
+        for field in self.setter_fields() {
+            let field_type = field.ty;
+            let mut setters = Vec::new();
+            let each_replaces_bulk = field
+                .builder_attr
+                .setter
+                .each
+                .as_ref()
+                .is_some_and(|each| each.name == field.setter_method_name());
+            if !each_replaces_bulk {
+                let (params, _) = self.setter_params(field)?;
+                let overridable = self.is_overridable(field);
+                let mut generics = self.generics.clone();
+                for f in self.included_fields() {
+                    if f.ordinal != field.ordinal || overridable {
+                        generics.params.push(f.generic_ty_param());
+                    }
+                }
+                let source_field_state = if overridable { field.type_ident() } else { empty_type() };
+                if let Some(fallback_name) = &field.builder_attr.setter.strip_option_fallback {
+                    setters.push(FlattenedSetter {
+                        method_name: fallback_name.clone(),
+                        method_generics: quote!(),
+                        param_types: vec![field_type.to_token_stream()],
+                        error_type: None,
+                        generics: generics.clone(),
+                        source_field_state: source_field_state.clone(),
+                        target_field_state: field.tuplized_type_ty_param(),
+                    });
+                }
+                if field.builder_attr.setter.maybe.is_some() {
+                    setters.push(FlattenedSetter {
+                        method_name: field.maybe_method_name(),
+                        method_generics: quote!(),
+                        param_types: vec![self.maybe_setter_param_type(field)?],
+                        error_type: None,
+                        generics: generics.clone(),
+                        source_field_state: source_field_state.clone(),
+                        target_field_state: field.maybe_state_type(crate_module_path),
+                    });
+                }
+                setters.push(FlattenedSetter {
+                    method_name: field.setter_method_name(),
+                    method_generics: self.setter_method_generics(field),
+                    param_types: params.into_iter().map(|(_, ty)| ty).collect(),
+                    error_type: self.setter_error_type(field),
+                    generics,
+                    source_field_state,
+                    target_field_state: field.tuplized_type_ty_param(),
+                });
+            }
+            if let Some(each) = &field.builder_attr.setter.each {
+                let (params, _) = self.each_setter_params(field, each);
+                let field_generic = &field.generic_ident;
+                let mut generics = self.generics.clone();
+                for f in self.included_fields() {
+                    generics.params.push(f.generic_ty_param());
+                }
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(syn::parse_quote!(#field_generic: #crate_module_path::Optional<#field_type>));
+                setters.push(FlattenedSetter {
+                    method_name: each.name.clone(),
+                    method_generics: quote!(),
+                    param_types: params.into_iter().map(|(_, ty)| ty).collect(),
+                    error_type: None,
+                    generics,
+                    source_field_state: field.type_ident(),
+                    target_field_state: field.tuplized_type_ty_param(),
+                });
+            }
+
+            for FlattenedSetter {
+                method_name,
+                method_generics,
+                param_types,
+                error_type,
+                generics: helper_generics,
+                source_field_state,
+                target_field_state,
+            } in setters
+            {
+                let helper_name = syn::Ident::new(
+                    &format!("{}_Setter_{}", builder_name, strip_raw_ident_prefix(method_name.to_string())),
+                    Span::call_site(),
+                );
+                let args = (0..param_types.len())
+                    .map(|i| Ident::new(&format!("__arg{}", i), Span::call_site()))
+                    .collect::<Vec<_>>();
+                let (helper_impl_generics, _, helper_where_clause) = helper_generics.split_for_impl();
+                let source_state = self.included_fields().map(|f| {
+                    if f.ordinal == field.ordinal {
+                        source_field_state.clone()
+                    } else {
+                        f.type_ident()
+                    }
+                });
+                let target_state = self.included_fields().map(|f| {
+                    if f.ordinal == field.ordinal {
+                        target_field_state.clone()
+                    } else {
+                        f.type_ident()
+                    }
+                });
+                let (helper_return_type, host_return_type, map_method) = if let Some(error_type) = &error_type {
+                    (
+                        quote!(::core::result::Result<Self::Output, #error_type>),
+                        quote!(::core::result::Result<Self::Replaced<<Self::Inner as #helper_name #ty_generics>::Output>, #error_type>),
+                        quote!(flatten_try_map),
+                    )
+                } else {
+                    (
+                        quote!(Self::Output),
+                        quote!(Self::Replaced<<Self::Inner as #helper_name #ty_generics>::Output>),
+                        quote!(flatten_map),
+                    )
+                };
+                let deprecated = &field.builder_attr.deprecated;
+
+                helpers.push(quote! {
+                    #[doc(hidden)]
+                    #[allow(dead_code, non_camel_case_types, missing_docs)]
+                    #vis trait #helper_name <#decl_generics> #where_clause {
+                        type Output;
+
+                        fn set #method_generics (self, #(#args: #param_types),*) -> #helper_return_type;
+                    }
+
+                    #[automatically_derived]
+                    impl #helper_impl_generics #helper_name #ty_generics for #builder_name < #(#generic_args,)* (#(#source_state,)*) > #helper_where_clause {
+                        type Output = #builder_name < #(#generic_args,)* (#(#target_state,)*) >;
+
+                        #[allow(deprecated)]
+                        fn set #method_generics (self, #(#args: #param_types),*) -> #helper_return_type {
+                            self.#method_name(#(#args),*)
+                        }
+                    }
+                });
+                methods.push(quote! {
+                    #deprecated
+                    fn #method_name #method_generics (self, #(#args: #param_types),*) -> #host_return_type
+                    where
+                        Self::Inner: #helper_name #ty_generics,
+                    {
+                        self.#map_method(|__inner| <Self::Inner as #helper_name #ty_generics>::set(__inner, #(#args),*))
+                    }
+                });
+            }
+        }
+
+        let setters_trait_name = syn::Ident::new(&format!("{}Setters", builder_name), Span::call_site());
+        let setters_trait_doc = format!(
+            "The setters of [`{}`], for builders that have a `#[builder(flatten)]` field of type [`{}`].",
+            builder_name, name,
+        );
+        let impl_generics = {
+            let mut generics = self.generics.clone();
+            generics
+                .params
+                .push(syn::parse_quote!(__Host: #crate_module_path::FlattenHost<#name #ty_generics>));
+            generics
+        };
+        let (impl_generics, _, _) = impl_generics.split_for_impl();
+        let (type_impl_generics, _, _) = self.generics.split_for_impl();
+
+        Ok(quote! {
+            #[automatically_derived]
+            impl #type_impl_generics #crate_module_path::Flattenable for #name #ty_generics #where_clause {}
+
+            #(#helpers)*
+
+            #[doc = #setters_trait_doc]
+            #[allow(dead_code, missing_docs)]
+            #vis trait #setters_trait_name <#decl_generics>: #crate_module_path::FlattenHost<#name #ty_generics> + ::core::marker::Sized #where_clause {
+                #(#methods)*
+            }
+
+            #[automatically_derived]
+            impl #impl_generics #setters_trait_name #ty_generics for __Host #where_clause {}
+        })
+    }
+
+    pub fn reset_impl(&self, field: &FieldInfo) -> TokenStream {
+        let StructInfo { ref builder_name, .. } = *self;
+        let field_name = &field.name;
+
+        let mut generics = self.generics.clone();
+        for f in self.included_fields() {
+            generics.params.push(f.generic_ty_param());
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let generic_args = self.generic_args();
+        let source_state = self.included_fields().map(|f| f.type_ident());
+        let target_state = self.included_fields().map(|f| {
+            if f.ordinal == field.ordinal {
+                empty_type()
+            } else {
+                f.type_ident()
+            }
+        });
+        let descructuring = self.included_fields().map(|f| {
+            if f.ordinal == field.ordinal {
+                quote!(_)
+            } else {
+                f.name.to_token_stream()
+            }
+        });
+        let reconstructing = self.included_fields().map(|f| &f.name);
+        let method_name = field.reset_method_name();
+        let doc = format!(
+            "Unset `{}`, so that its default is used unless it is set again.",
+            strip_raw_ident_prefix(field_name.to_string()),
+        );
 
-        Ok(quote! {
+        quote! {
             #[allow(dead_code, non_camel_case_types, missing_docs)]
             #[automatically_derived]
-            impl #impl_generics #builder_name < #( #ty_generics ),* > #where_clause {
-                #deprecated
-                #doc
+            impl #impl_generics #builder_name < #(#generic_args,)* (#(#source_state,)*) > #where_clause {
+                #[doc = #doc]
                 #[allow(clippy::used_underscore_binding)]
-                pub fn #method_name (self, #param_list) -> #builder_name <#( #target_generics ),*> {
-                    let #field_name = (#arg_expr,);
+                pub fn #method_name(self) -> #builder_name < #(#generic_args,)* (#(#target_state,)*) > {
                     let ( #(#descructuring,)* ) = self.fields;
+                    let #field_name = ();
                     #builder_name {
                         fields: ( #(#reconstructing,)* ),
                         phantom: self.phantom,
                     }
                 }
             }
-            #[doc(hidden)]
-            #[allow(dead_code, non_camel_case_types, non_snake_case)]
-            #[allow(clippy::exhaustive_enums)]
-            pub enum #repeated_fields_error_type_name {}
-            #[doc(hidden)]
-            #[allow(dead_code, non_camel_case_types, missing_docs)]
-            #[automatically_derived]
-            impl #impl_generics #builder_name < #( #target_generics ),* > #where_clause {
-                #[deprecated(
-                    note = #repeated_fields_error_message
-                )]
-                pub fn #method_name (self, _: #repeated_fields_error_type_name) -> #builder_name <#( #target_generics ),*> {
-                    self
-                }
-            }
-        })
+        }
     }
 
     pub fn required_field_impl(&self, field: &FieldInfo) -> TokenStream {
@@ -306,7 +2402,7 @@ impl<'a> StructInfo<'a> {
         let FieldInfo {
             name: ref field_name, ..
         } = field;
-        let mut builder_generics: Vec<syn::GenericArgument> = self
+        let builder_generics: Vec<syn::GenericArgument> = self
             .generics
             .params
             .iter()
@@ -322,42 +2418,6 @@ impl<'a> StructInfo<'a> {
                 }
             })
             .collect();
-        let mut builder_generics_tuple = empty_type_tuple();
-        let generics = {
-            let mut generics = self.generics.clone();
-            for f in self.included_fields() {
-                if f.builder_attr.default.is_some() {
-                    // `f` is not mandatory - it does not have it's own fake `build` method, so `field` will need
-                    // to warn about missing `field` whether or not `f` is set.
-                    assert!(
-                        f.ordinal != field.ordinal,
-                        "`required_field_impl` called for optional field {}",
-                        field.name
-                    );
-                    generics.params.push(f.generic_ty_param());
-                    builder_generics_tuple.elems.push_value(f.type_ident());
-                } else if f.ordinal < field.ordinal {
-                    // Only add a `build` method that warns about missing `field` if `f` is set. If `f` is not set,
-                    // `f`'s `build` method will warn, since it appears earlier in the argument list.
-                    builder_generics_tuple.elems.push_value(f.tuplized_type_ty_param());
-                } else if f.ordinal == field.ordinal {
-                    builder_generics_tuple.elems.push_value(empty_type());
-                } else {
-                    // `f` appears later in the argument list after `field`, so if they are both missing we will
-                    // show a warning for `field` and not for `f` - which means this warning should appear whether
-                    // or not `f` is set.
-                    generics.params.push(f.generic_ty_param());
-                    builder_generics_tuple.elems.push_value(f.type_ident());
-                }
-
-                builder_generics_tuple.elems.push_punct(Default::default());
-            }
-            generics
-        };
-
-        builder_generics.push(syn::GenericArgument::Type(builder_generics_tuple.into()));
-        let (impl_generics, _, where_clause) = generics.split_for_impl();
-
         let early_build_error_type_name = syn::Ident::new(
             &format!(
                 "{}_Error_Missing_required_field_{}",
@@ -366,27 +2426,87 @@ impl<'a> StructInfo<'a> {
             ),
             proc_macro2::Span::call_site(),
         );
-        let early_build_error_message = format!("Missing required field {}", field_name);
-
         let build_method_name = self.build_method_name();
         let build_method_visibility = self.build_method_visibility();
+        let build_with_method_name = self.build_with_method_name();
+
+        let early_build_error_message = if field.builder_attr.default_from_ctx.is_some() {
+            format!(
+                "Missing required field {} - set it or use {}",
+                field_name, build_with_method_name
+            )
+        } else {
+            format!("Missing required field {}", field_name)
+        };
+
+        // Fields with `default_from_ctx` are only required by `build`, not by `build_with`.
+        let fake_builds = self
+            .build_variants()
+            .filter(|&with_ctx| !(with_ctx && field.builder_attr.default_from_ctx.is_some()))
+            .map(|with_ctx| {
+                let mut builder_generics = builder_generics.clone();
+                let mut builder_generics_tuple = empty_type_tuple();
+                let generics = {
+                    let mut generics = self.generics.clone();
+                    for f in self.included_fields() {
+                        if Self::has_default(f, with_ctx) || f.builder_attr.flatten.is_some() {
+                            // `f` is not mandatory - it does not have it's own fake `build` method, so `field` will need
+                            // to warn about missing `field` whether or not `f` is set.
+                            assert!(
+                                f.ordinal != field.ordinal,
+                                "`required_field_impl` called for optional field {}",
+                                field.name
+                            );
+                            generics.params.push(f.generic_ty_param());
+                            builder_generics_tuple.elems.push_value(f.type_ident());
+                        } else if f.ordinal < field.ordinal {
+                            // Only add a `build` method that warns about missing `field` if `f` is set. If `f` is not set,
+                            // `f`'s `build` method will warn, since it appears earlier in the argument list.
+                            builder_generics_tuple.elems.push_value(f.tuplized_type_ty_param());
+                        } else if f.ordinal == field.ordinal {
+                            builder_generics_tuple.elems.push_value(empty_type());
+                        } else {
+                            // `f` appears later in the argument list after `field`, so if they are both missing we will
+                            // show a warning for `field` and not for `f` - which means this warning should appear whether
+                            // or not `f` is set.
+                            generics.params.push(f.generic_ty_param());
+                            builder_generics_tuple.elems.push_value(f.type_ident());
+                        }
+
+                        builder_generics_tuple.elems.push_punct(Default::default());
+                    }
+                    generics
+                };
+
+                builder_generics.push(syn::GenericArgument::Type(builder_generics_tuple.into()));
+                let (impl_generics, _, where_clause) = generics.split_for_impl();
+                let method_name = if with_ctx {
+                    build_with_method_name.to_token_stream()
+                } else {
+                    build_method_name.clone()
+                };
+
+                quote! {
+                    #[doc(hidden)]
+                    #[allow(dead_code, non_camel_case_types, missing_docs, clippy::panic)]
+                    #[automatically_derived]
+                    impl #impl_generics #builder_name < #( #builder_generics ),* > #where_clause {
+                        #[deprecated(
+                            note = #early_build_error_message
+                        )]
+                        #build_method_visibility fn #method_name(self, _: #early_build_error_type_name) -> ! {
+                            panic!()
+                        }
+                    }
+                }
+            });
 
         quote! {
             #[doc(hidden)]
             #[allow(dead_code, non_camel_case_types, non_snake_case)]
             #[allow(clippy::exhaustive_enums)]
             pub enum #early_build_error_type_name {}
-            #[doc(hidden)]
-            #[allow(dead_code, non_camel_case_types, missing_docs, clippy::panic)]
-            #[automatically_derived]
-            impl #impl_generics #builder_name < #( #builder_generics ),* > #where_clause {
-                #[deprecated(
-                    note = #early_build_error_message
-                )]
-                #build_method_visibility fn #build_method_name(self, _: #early_build_error_type_name) -> ! {
-                    panic!()
-                }
-            }
+            #( #fake_builds )*
         }
     }
 
@@ -398,17 +2518,82 @@ impl<'a> StructInfo<'a> {
         first_visibility(&[self.builder_attr.build_method.common.vis.as_ref(), Some(&public_visibility())])
     }
 
-    pub fn build_method_impl(&self) -> TokenStream {
+    /// The generic parameters, output type and where predicates of the build method, which depend on
+    /// its `into`/`try_into` conversion and on whether it is fallible.
+    fn build_method_signature(&self) -> (Option<TokenStream>, TokenStream, Vec<TokenStream>) {
+        let name = self.name;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        let built_type = quote!(#name #ty_generics);
+        let (build_method_generic, target_type) = match &self.builder_attr.build_method.into {
+            IntoSetting::NoConversion => (None, built_type.clone()),
+            IntoSetting::GenericConversion | IntoSetting::GenericTryConversion => (Some(quote!(<__R>)), quote!(__R)),
+            IntoSetting::TypeConversionToSpecificType(into) | IntoSetting::TryConversionToSpecificType(into) => {
+                (None, into.to_token_stream())
+            }
+        };
+        let mut where_predicates = Vec::new();
+        let output_type = match (&self.builder_attr.build_method.into, &self.builder_attr.build_method.error) {
+            (IntoSetting::GenericTryConversion | IntoSetting::TryConversionToSpecificType(_), error) => {
+                let conversion_error = quote!(<#built_type as ::core::convert::TryInto<#target_type>>::Error);
+                if let IntoSetting::GenericTryConversion = self.builder_attr.build_method.into {
+                    where_predicates.push(quote!(#built_type: ::core::convert::TryInto<__R>));
+                }
+                if let Some(error) = error {
+                    if let IntoSetting::GenericTryConversion = self.builder_attr.build_method.into {
+                        where_predicates.push(quote!(#error: ::core::convert::From<#conversion_error>));
+                    }
+                    quote!(::core::result::Result<#target_type, #error>)
+                } else {
+                    quote!(::core::result::Result<#target_type, #conversion_error>)
+                }
+            }
+            (IntoSetting::GenericConversion, error) => {
+                where_predicates.push(quote!(#built_type: Into<__R>));
+                if let Some(error) = error {
+                    quote!(::core::result::Result<#target_type, #error>)
+                } else {
+                    target_type
+                }
+            }
+            (_, Some(error)) => quote!(::core::result::Result<#target_type, #error>),
+            (_, None) => target_type,
+        };
+        (build_method_generic, output_type, where_predicates)
+    }
+
+    pub fn build_method_impl(&self) -> Result<TokenStream, Error> {
         let StructInfo {
             ref name,
             ref builder_name,
             ..
         } = *self;
 
-        let generics = {
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        // The constraints between the fields set by groups, `requires` or `conflicts_with` are
+        // checked with a bound on a marker trait for each of them, and the states that violate them
+        // get deprecated overloads that explain why.
+        let (constraint_traits, constraint_traits_impl) = self.constraint_traits_impl();
+        let constraint_violations = self.constraint_violations_impl(&constraint_traits);
+        let mut impl_headers = Vec::new();
+        for with_ctx in self.build_variants() {
             let mut generics = self.generics.clone();
             for field in self.included_fields() {
-                if field.builder_attr.default.is_some() {
+                if field.builder_attr.flatten.is_some() {
+                    let crate_module_path = &self.builder_attr.crate_module_path;
+                    let field_type = field.ty;
+                    let mut generic_param: syn::TypeParam = field.generic_ident.clone().into();
+                    generic_param
+                        .bounds
+                        .push(syn::parse_quote!(#crate_module_path::FinishBuild<#field_type>));
+                    generics.params.push(generic_param.into());
+                } else if Self::has_default(field, with_ctx) {
+                    // Async defaults can't be awaited in the closure `Optional` takes.
+                    let trait_name = if field.builder_attr.default_async.is_some() {
+                        "FieldState"
+                    } else {
+                        "Optional"
+                    };
                     let trait_ref = syn::TraitBound {
                         paren_token: None,
                         lifetimes: None,
@@ -416,7 +2601,7 @@ impl<'a> StructInfo<'a> {
                         path: {
                             let mut path = self.builder_attr.crate_module_path.clone();
                             path.segments.push(syn::PathSegment {
-                                ident: Ident::new("Optional", Span::call_site()),
+                                ident: Ident::new(trait_name, Span::call_site()),
                                 arguments: syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
                                     colon2_token: None,
                                     lt_token: Default::default(),
@@ -432,46 +2617,81 @@ impl<'a> StructInfo<'a> {
                     generics.params.push(generic_param.into());
                 }
             }
-            generics
-        };
-        let (impl_generics, _, _) = generics.split_for_impl();
 
-        let (_, ty_generics, where_clause) = self.generics.split_for_impl();
+            let modified_ty_generics = modify_types_generics_hack(&ty_generics, |args| {
+                args.push(syn::GenericArgument::Type(
+                    type_tuple(self.included_fields().map(|field| {
+                        if Self::has_default(field, with_ctx) || field.builder_attr.flatten.is_some() {
+                            field.type_ident()
+                        } else {
+                            field.tuplized_type_ty_param()
+                        }
+                    }))
+                    .into(),
+                ));
+            });
+            for constraint_trait in &constraint_traits {
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(syn::parse_quote!(#builder_name #modified_ty_generics: #constraint_trait));
+            }
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            impl_headers.push((
+                with_ctx,
+                impl_generics.to_token_stream(),
+                modified_ty_generics,
+                where_clause.to_token_stream(),
+            ));
+        }
 
-        let modified_ty_generics = modify_types_generics_hack(&ty_generics, |args| {
-            args.push(syn::GenericArgument::Type(
-                type_tuple(self.included_fields().map(|field| {
-                    if field.builder_attr.default.is_some() {
-                        field.type_ident()
+        let descructuring = self.included_fields().map(|f| &f.name).collect::<Vec<_>>();
+
+        // The default of a field can refer to other fields, which we handle by writing out a bunch
+        // of `let` statements first, ordered so that each comes after the fields it refers to.
+        let assignment_order = self.default_assignment_order()?;
+        let assignments = |with_ctx: bool| {
+            assignment_order.iter().map(move |field| {
+                let name = &field.name;
+                if field.builder_attr.flatten.is_some() {
+                    let crate_module_path = &self.builder_attr.crate_module_path;
+                    quote!(let #name = #crate_module_path::FinishBuild::finish_build(#name);)
+                } else if let Some(ref default) = field.builder_attr.default {
+                    if field.builder_attr.setter.skip.is_some() {
+                        if field.builder_attr.default_async.is_some() {
+                            quote!(let #name = (#default).await;)
+                        } else {
+                            quote!(let #name = #default;)
+                        }
+                    } else if field.builder_attr.default_async.is_some() {
+                        let crate_module_path = &self.builder_attr.crate_module_path;
+                        quote! {
+                            let #name = match #crate_module_path::FieldState::into_option(#name) {
+                                ::core::option::Option::Some(#name) => #name,
+                                ::core::option::Option::None => (#default).await,
+                            };
+                        }
                     } else {
-                        field.tuplized_type_ty_param()
+                        let crate_module_path = &self.builder_attr.crate_module_path;
+                        quote!(let #name = #crate_module_path::Optional::into_value(#name, || #default);)
                     }
-                }))
-                .into(),
-            ));
-        });
-
-        let descructuring = self.included_fields().map(|f| f.name);
-
-        // The default of a field can refer to earlier-defined fields, which we handle by
-        // writing out a bunch of `let` statements first, which can each refer to earlier ones.
-        // This means that field ordering may actually be significant, which isn't ideal. We could
-        // relax that restriction by calculating a DAG of field default dependencies and
-        // reordering based on that, but for now this much simpler thing is a reasonable approach.
-        let assignments = self.fields.iter().map(|field| {
-            let name = &field.name;
-            if let Some(ref default) = field.builder_attr.default {
-                if field.builder_attr.setter.skip.is_some() {
-                    quote!(let #name = #default;)
-                } else {
+                } else if let (Some(default_from_ctx), true) = (&field.builder_attr.default_from_ctx, with_ctx) {
                     let crate_module_path = &self.builder_attr.crate_module_path;
-                    quote!(let #name = #crate_module_path::Optional::into_value(#name, || #default);)
+                    quote! {
+                        #[allow(clippy::redundant_closure_call)]
+                        let #name = #crate_module_path::Optional::into_value(#name, || (#default_from_ctx)(__ctx));
+                    }
+                } else {
+                    quote!(let #name = #name.0;)
                 }
-            } else {
-                quote!(let #name = #name.0;)
-            }
-        });
-        let field_names = self.fields.iter().map(|field| field.name);
+            })
+        };
+        let field_names = self.fields.iter().map(|field| &field.name);
+        let constructor_fields = if self.is_tuple {
+            quote!(( #( #field_names ),* ))
+        } else {
+            quote!({ #( #field_names ),* })
+        };
 
         let build_method_name = self.build_method_name();
         let build_method_visibility = self.build_method_visibility();
@@ -479,46 +2699,256 @@ impl<'a> StructInfo<'a> {
             self.builder_attr
                 .build_method
                 .common
-                .get_doc_or(|| format!("Finalise the builder and create its [`{}`] instance", name))
+                .get_doc_or(|| format!("Finalise the builder and create its [`{}`] instance", self.target_name()))
         } else {
             quote!()
         };
 
         let type_constructor = {
             let ty_generics = ty_generics.as_turbofish();
-            quote!(#name #ty_generics)
+            if let Some(variant) = self.variant {
+                quote!(#name #ty_generics :: #variant)
+            } else {
+                quote!(#name #ty_generics)
+            }
         };
 
-        let (build_method_generic, output_type, build_method_where_clause) = match &self.builder_attr.build_method.into {
-            IntoSetting::NoConversion => (None, quote!(#name #ty_generics), None),
-            IntoSetting::GenericConversion => (
-                Some(quote!(<__R>)),
-                quote!(__R),
-                Some(quote!(where #name #ty_generics: Into<__R>)),
-            ),
-            IntoSetting::TypeConversionToSpecificType(into) => (None, into.to_token_stream(), None),
+        let built_type = quote!(#name #ty_generics);
+        let (build_method_generic, output_type, build_method_where_predicates) = self.build_method_signature();
+
+        let build_value = quote! {
+            #[allow(deprecated)]
+            let __value = #type_constructor #constructor_fields;
+        };
+        let validation = self.builder_attr.build_method.validate.as_ref().map(|validate| {
+            quote! {
+                #[allow(clippy::redundant_closure_call)]
+                (#validate)(&__value)?;
+            }
+        });
+        let validation_async = self.builder_attr.build_method.validate_async.as_ref().map(|validate_async| {
+            quote! {
+                #[allow(clippy::redundant_closure_call)]
+                (#validate_async)(&__value).await?;
+            }
+        });
+        let validation = quote!(#validation #validation_async);
+
+        let build_body = match (&self.builder_attr.build_method.into, &self.builder_attr.build_method.error) {
+            (IntoSetting::GenericTryConversion | IntoSetting::TryConversionToSpecificType(_), Some(_)) => quote! {
+                #build_value
+                #validation
+                ::core::result::Result::Ok(::core::convert::TryInto::try_into(__value)?)
+            },
+            (IntoSetting::GenericTryConversion | IntoSetting::TryConversionToSpecificType(_), None) => quote! {
+                #build_value
+                ::core::convert::TryInto::try_into(__value)
+            },
+            (_, Some(_)) => quote! {
+                #build_value
+                #validation
+                ::core::result::Result::Ok(__value.into())
+            },
+            (_, None) => quote! {
+                #[allow(deprecated)]
+                #type_constructor #constructor_fields.into()
+            },
+        };
+        let build_method_where_clause = if build_method_where_predicates.is_empty() {
+            None
+        } else {
+            Some(quote!(where #( #build_method_where_predicates ),*))
         };
 
-        quote!(
-            #[allow(dead_code, non_camel_case_types, missing_docs)]
-            #[automatically_derived]
-            impl #impl_generics #builder_name #modified_ty_generics #where_clause {
-                #build_method_doc
-                #[allow(clippy::default_trait_access, clippy::used_underscore_binding)]
-                #build_method_visibility fn #build_method_name #build_method_generic (self) -> #output_type #build_method_where_clause {
-                    let ( #(#descructuring,)* ) = self.fields;
-                    #( #assignments )*
+        let finish_build = self.variant.is_none()
+            && self.builder_attr.build_method.asyncness.is_none()
+            && self.builder_attr.build_method.common.vis.is_none()
+            && matches!(self.builder_attr.build_method.into, IntoSetting::NoConversion)
+            && self.builder_attr.build_method.error.is_none();
+        let crate_module_path = &self.builder_attr.crate_module_path;
+        let build_with_method_name = self.build_with_method_name();
+        let asyncness = self
+            .builder_attr
+            .build_method
+            .asyncness
+            .map(|span| quote_spanned!(span=> async));
+        let build_impls = impl_headers.iter().map(|(with_ctx, impl_generics, modified_ty_generics, where_clause)| {
+            let assignments = assignments(*with_ctx);
+            if *with_ctx {
+                let context = &self.builder_attr.build_method.context;
+                return quote! {
+                    #[allow(dead_code, non_camel_case_types, missing_docs)]
+                    #[automatically_derived]
+                    impl #impl_generics #builder_name #modified_ty_generics #where_clause {
+                        #build_method_doc
+                        #[allow(clippy::default_trait_access, clippy::used_underscore_binding)]
+                        #build_method_visibility #asyncness fn #build_with_method_name #build_method_generic (self, __ctx: &#context) -> #output_type #build_method_where_clause {
+                            let ( #(#descructuring,)* ) = self.fields;
+                            #( #assignments )*
+
+                            #build_body
+                        }
+                    }
+                };
+            }
+            let finish_build_impl = if finish_build {
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics #crate_module_path::FinishBuild<#built_type> for #builder_name #modified_ty_generics #where_clause {
+                        fn finish_build(self) -> #built_type {
+                            self.#build_method_name()
+                        }
+                    }
+                }
+            } else {
+                quote!()
+            };
+            quote! {
+                #finish_build_impl
 
-                    #[allow(deprecated)]
-                    #type_constructor {
-                        #( #field_names ),*
-                    }.into()
+                #[allow(dead_code, non_camel_case_types, missing_docs)]
+                #[automatically_derived]
+                impl #impl_generics #builder_name #modified_ty_generics #where_clause {
+                    #build_method_doc
+                    #[allow(clippy::default_trait_access, clippy::used_underscore_binding)]
+                    #build_method_visibility #asyncness fn #build_method_name #build_method_generic (self) -> #output_type #build_method_where_clause {
+                        let ( #(#descructuring,)* ) = self.fields;
+                        #( #assignments )*
+
+                        #build_body
+                    }
                 }
             }
-        )
+        });
+
+        Ok(quote!(
+            #constraint_traits_impl
+            #constraint_violations
+            #( #build_impls )*
+        ))
+    }
+
+    /// Whether the build method can be called while `field` is unset - `with_ctx` being whether it
+    /// is the `build_with` method, which also resolves the defaults computed from the context.
+    fn has_default(field: &FieldInfo, with_ctx: bool) -> bool {
+        field.builder_attr.default.is_some() || (with_ctx && field.builder_attr.default_from_ctx.is_some())
+    }
+
+    /// The variants of the build method, by whether they take a context - `build`, and `build_with`
+    /// if the context type is set.
+    fn build_variants(&self) -> impl Iterator<Item = bool> {
+        let with_ctx = self.builder_attr.build_method.context.is_some().then_some(true);
+        [false].into_iter().chain(with_ctx)
+    }
+
+    fn build_with_method_name(&self) -> Ident {
+        Ident::new(&format!("{}_with", self.build_method_name()), Span::call_site())
+    }
+
+    /// The other fields the default of `field` refers to - set with `default` or
+    /// `default_from_ctx`.
+    fn default_dependencies(&self, field: &FieldInfo) -> Vec<&FieldInfo<'a>> {
+        let Some(default) = (field.builder_attr.default.as_ref()).or(field.builder_attr.default_from_ctx.as_ref()) else {
+            return Vec::new();
+        };
+        let idents = local_idents(default);
+        self.fields
+            .iter()
+            .filter(|f| f.ordinal != field.ordinal && idents.contains(&f.name))
+            .collect()
+    }
+
+    /// The fields in the order their values are resolved in the build method - every field after
+    /// the fields its default refers to, and otherwise in declaration order.
+    fn default_assignment_order(&self) -> Result<Vec<&FieldInfo<'a>>, Error> {
+        enum Visit {
+            InProgress,
+            Done,
+        }
+
+        fn visit<'s, 'a>(
+            struct_info: &'s StructInfo<'a>,
+            field: &'s FieldInfo<'a>,
+            visits: &mut [Option<Visit>],
+            path: &mut Vec<&'s FieldInfo<'a>>,
+            order: &mut Vec<&'s FieldInfo<'a>>,
+        ) -> Result<(), Error> {
+            match visits[field.ordinal] {
+                Some(Visit::Done) => return Ok(()),
+                Some(Visit::InProgress) => {
+                    let cycle_start = path.iter().position(|f| f.ordinal == field.ordinal).unwrap_or_default();
+                    let cycle = &path[cycle_start..];
+                    let cycle_text = cycle
+                        .iter()
+                        .chain([&field])
+                        .map(|f| strip_raw_ident_prefix(f.name.to_string()))
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    let mut errors = cycle.iter().map(|f| {
+                        Error::new_spanned(
+                            &f.name,
+                            format_args!("the default of {} is part of a dependency cycle: {}", f.name, cycle_text),
+                        )
+                    });
+                    let mut error = errors.next().expect("a cycle has at least one field");
+                    errors.for_each(|e| error.combine(e));
+                    return Err(error);
+                }
+                None => {}
+            }
+            visits[field.ordinal] = Some(Visit::InProgress);
+            path.push(field);
+            for dependency in struct_info.default_dependencies(field) {
+                visit(struct_info, dependency, visits, path, order)?;
+            }
+            path.pop();
+            visits[field.ordinal] = Some(Visit::Done);
+            order.push(field);
+            Ok(())
+        }
+
+        let mut visits = self.fields.iter().map(|_| None).collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            visit(self, field, &mut visits, &mut Vec::new(), &mut order)?;
+        }
+        Ok(order)
     }
 }
 
+/// A constraint between the fields, set by groups, `requires` or `conflicts_with`.
+struct FieldConstraint<'s, 'a> {
+    /// What the constraint requires, used for the name of its marker trait.
+    description: String,
+    /// The builder states the constraint allows, each given by whether some of the fields are set -
+    /// the rest may be in any state.
+    allowed_states: Vec<Vec<(&'s FieldInfo<'a>, bool)>>,
+    /// A state the constraint doesn't allow, which gets a deprecated overload of the build method.
+    violation: Option<ConstraintViolation<'s, 'a>>,
+}
+
+/// A builder state that violates a constraint between the fields.
+struct ConstraintViolation<'s, 'a> {
+    /// The deprecation note of the build method overload, explaining the violation.
+    note: String,
+    /// Whether some of the fields are set in the violating state - the rest may be in any state.
+    state: Vec<(&'s FieldInfo<'a>, bool)>,
+}
+
+/// A setter of the builder, as exposed through the setters trait used for flattening.
+struct FlattenedSetter {
+    method_name: Ident,
+    method_generics: TokenStream,
+    param_types: Vec<TokenStream>,
+    error_type: Option<TokenStream>,
+    /// The generics of the builder states the setter can be called on.
+    generics: syn::Generics,
+    /// The state the setter can be called on for its field.
+    source_field_state: syn::Type,
+    /// The state the setter moves its field to.
+    target_field_state: syn::Type,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct CommonDeclarationSettings {
     pub vis: Option<syn::Visibility>,
@@ -573,20 +3003,19 @@ impl CommonDeclarationSettings {
 }
 
 /// Setting of the `into` argument.
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub enum IntoSetting {
     /// Do not run any conversion on the built value.
+    #[default]
     NoConversion,
     /// Convert the build value into the generic parameter passed to the `build` method.
     GenericConversion,
     /// Convert the build value into a specific type specified in the attribute.
     TypeConversionToSpecificType(syn::ExprPath),
-}
-
-impl Default for IntoSetting {
-    fn default() -> Self {
-        Self::NoConversion
-    }
+    /// Fallibly convert the build value into the generic parameter passed to the `build` method.
+    GenericTryConversion,
+    /// Fallibly convert the build value into a specific type specified in the attribute.
+    TryConversionToSpecificType(syn::ExprPath),
 }
 
 #[derive(Debug, Default, Clone)]
@@ -595,6 +3024,23 @@ pub struct BuildMethodSettings {
 
     /// Whether to convert the built type into another while finishing the build.
     pub into: IntoSetting,
+
+    /// A function to check the built value with before returning it. When set, the build method
+    /// returns a `Result`.
+    pub validate: Option<syn::Expr>,
+
+    /// The error type of the build method's `Result`, when `validate` is set.
+    pub error: Option<syn::ExprPath>,
+
+    /// The type of the context passed to the `build_with` method, which `default_from_ctx` fields
+    /// compute their defaults from.
+    pub context: Option<syn::Type>,
+
+    /// Whether the build method is `async`, set with `build_method(async)`.
+    pub asyncness: Option<Span>,
+
+    /// An async function to check the built value with, like `validate`.
+    pub validate_async: Option<syn::Expr>,
 }
 
 impl BuildMethodSettings {
@@ -603,24 +3049,54 @@ impl BuildMethodSettings {
             syn::Expr::Assign(assign) => {
                 let name =
                     expr_to_single_string(&assign.left).ok_or_else(|| Error::new_spanned(&assign.left, "Expected identifier"))?;
-                if name.as_str() == "into" {
-                    let expr_path = match assign.right.as_ref() {
-                        syn::Expr::Path(expr_path) => expr_path,
-                        _ => return Err(Error::new_spanned(&assign.right, "Expected path expression type")),
-                    };
-                    self.into = IntoSetting::TypeConversionToSpecificType(expr_path.clone());
-                    Ok(())
-                } else {
-                    self.common.apply_meta(expr)
+                match name.as_str() {
+                    "into" | "try_into" => {
+                        let expr_path = match assign.right.as_ref() {
+                            syn::Expr::Path(expr_path) => expr_path.clone(),
+                            _ => return Err(Error::new_spanned(&assign.right, "Expected path expression type")),
+                        };
+                        self.into = if name == "into" {
+                            IntoSetting::TypeConversionToSpecificType(expr_path)
+                        } else {
+                            IntoSetting::TryConversionToSpecificType(expr_path)
+                        };
+                        Ok(())
+                    }
+                    "validate" => {
+                        self.validate = Some(*assign.right.clone());
+                        Ok(())
+                    }
+                    "validate_async" => {
+                        self.validate_async = Some(*assign.right.clone());
+                        Ok(())
+                    }
+                    "error" => {
+                        let expr_path = match assign.right.as_ref() {
+                            syn::Expr::Path(expr_path) => expr_path,
+                            _ => return Err(Error::new_spanned(&assign.right, "Expected path expression type")),
+                        };
+                        self.error = Some(expr_path.clone());
+                        Ok(())
+                    }
+                    "context" => {
+                        self.context = Some(syn::parse2(assign.right.to_token_stream())?);
+                        Ok(())
+                    }
+                    _ => self.common.apply_meta(expr),
                 }
             }
             syn::Expr::Path(path) => {
                 let name = path_to_single_string(&path.path).ok_or_else(|| Error::new_spanned(path, "Expected identifier"))?;
-                if name.as_str() == "into" {
-                    self.into = IntoSetting::GenericConversion;
-                    Ok(())
-                } else {
-                    self.common.apply_meta(expr)
+                match name.as_str() {
+                    "into" => {
+                        self.into = IntoSetting::GenericConversion;
+                        Ok(())
+                    }
+                    "try_into" => {
+                        self.into = IntoSetting::GenericTryConversion;
+                        Ok(())
+                    }
+                    _ => self.common.apply_meta(expr),
                 }
             }
             _ => self.common.apply_meta(expr),
@@ -628,7 +3104,7 @@ impl BuildMethodSettings {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TypeBuilderAttr<'a> {
     /// Whether to show docs for the `TypeBuilder` type (rather than hiding them).
     pub doc: bool,
@@ -645,6 +3121,19 @@ pub struct TypeBuilderAttr<'a> {
     pub field_defaults: FieldBuilderAttr<'a>,
 
     pub crate_module_path: syn::Path,
+
+    /// Generate `into_builder` and `to_builder` methods on the built type.
+    pub into_builder: Option<Span>,
+
+    /// Also generate a runtime-checked builder, with `&mut self` setters.
+    pub dynamic: Option<Span>,
+
+    /// Implement `Deserialize` for the builder in the state where only the fields with a default
+    /// are set.
+    pub deserialize: Option<Span>,
+
+    /// Generate the setters trait that lets other builders hold this one in a `flatten` field.
+    pub flattenable: Option<Span>,
 }
 
 impl Default for TypeBuilderAttr<'_> {
@@ -656,14 +3145,20 @@ impl Default for TypeBuilderAttr<'_> {
             build_method: Default::default(),
             field_defaults: Default::default(),
             crate_module_path: syn::parse_quote!(::typed_builder),
+            into_builder: None,
+            dynamic: None,
+            deserialize: None,
+            flattenable: None,
         }
     }
 }
 
 impl<'a> TypeBuilderAttr<'a> {
     pub fn new(attrs: &[syn::Attribute]) -> Result<Self, Error> {
-        let mut result = Self::default();
+        Self::default().with(attrs)
+    }
 
+    pub fn with(mut self, attrs: &[syn::Attribute]) -> Result<Self, Error> {
         for attr in attrs {
             let list = match &attr.meta {
                 syn::Meta::List(list) => {
@@ -676,14 +3171,63 @@ impl<'a> TypeBuilderAttr<'a> {
                 _ => continue,
             };
 
-            apply_subsections(list, |expr| result.apply_meta(expr))?;
+            if list.tokens.is_empty() {
+                return Err(Error::new_spanned(list, "Expected builder(…)"));
+            }
+            let settings = list.parse_args_with(Punctuated::<TypeBuilderSetting, syn::Token![,]>::parse_terminated)?;
+            for setting in settings {
+                match setting {
+                    TypeBuilderSetting::BuildMethod(build_method_settings) => {
+                        for build_method_setting in build_method_settings {
+                            match build_method_setting {
+                                BuildMethodSetting::Async(asyncness) => self.build_method.asyncness = Some(asyncness.span),
+                                BuildMethodSetting::Other(expr) => self.build_method.apply_meta(expr)?,
+                            }
+                        }
+                    }
+                    TypeBuilderSetting::Other(expr) => self.apply_meta(expr)?,
+                }
+            }
+        }
+
+        if self.builder_type.doc.is_some() || self.build_method.common.doc.is_some() {
+            self.doc = true;
         }
 
-        if result.builder_type.doc.is_some() || result.build_method.common.doc.is_some() {
-            result.doc = true;
+        if let (Some(validate), None) = (&self.build_method.validate, &self.build_method.error) {
+            return Err(Error::new_spanned(
+                validate,
+                "build_method(validate = ...) must be accompanied by build_method(error = ...)",
+            ));
+        }
+        if let (Some(validate_async), None) = (&self.build_method.validate_async, &self.build_method.error) {
+            return Err(Error::new_spanned(
+                validate_async,
+                "build_method(validate_async = ...) must be accompanied by build_method(error = ...)",
+            ));
+        }
+        if let (Some(validate_async), None) = (&self.build_method.validate_async, self.build_method.asyncness) {
+            return Err(Error::new_spanned(
+                validate_async,
+                "build_method(validate_async = ...) requires build_method(async)",
+            ));
+        }
+        if let (None, None, Some(error)) = (
+            &self.build_method.validate,
+            &self.build_method.validate_async,
+            &self.build_method.error,
+        ) {
+            if let IntoSetting::NoConversion | IntoSetting::GenericConversion | IntoSetting::TypeConversionToSpecificType(_) =
+                self.build_method.into
+            {
+                return Err(Error::new_spanned(
+                    error,
+                    "build_method(error = ...) requires build_method(validate = ...), build_method(validate_async = ...) or build_method(try_into)",
+                ));
+            }
         }
 
-        Ok(result)
+        Ok(self)
     }
 
     fn apply_meta(&mut self, expr: syn::Expr) -> Result<(), Error> {
@@ -723,6 +3267,22 @@ impl<'a> TypeBuilderAttr<'a> {
                         self.doc = true;
                         Ok(())
                     }
+                    "into_builder" => {
+                        self.into_builder = Some(path.span());
+                        Ok(())
+                    }
+                    "dynamic" => {
+                        self.dynamic = Some(path.span());
+                        Ok(())
+                    }
+                    "deserialize" => {
+                        self.deserialize = Some(path.span());
+                        Ok(())
+                    }
+                    "flattenable" => {
+                        self.flattenable = Some(path.span());
+                        Ok(())
+                    }
                     _ => Err(Error::new_spanned(&path, format!("Unknown parameter {:?}", name))),
                 }
             }
@@ -756,12 +3316,6 @@ impl<'a> TypeBuilderAttr<'a> {
                         }
                         Ok(())
                     }
-                    "build_method" => {
-                        for arg in call.args {
-                            self.build_method.apply_meta(arg)?;
-                        }
-                        Ok(())
-                    }
                     _ => Err(Error::new_spanned(
                         &call.func,
                         format!("Illegal builder setting group name {}", subsetting_name),
@@ -772,3 +3326,39 @@ impl<'a> TypeBuilderAttr<'a> {
         }
     }
 }
+
+/// A setting in the `#[builder(...)]` attribute of the type. `build_method(...)` is parsed on its
+/// own, since it can contain the `async` keyword, which is not an expression.
+enum TypeBuilderSetting {
+    BuildMethod(Punctuated<BuildMethodSetting, syn::Token![,]>),
+    Other(syn::Expr),
+}
+
+impl Parse for TypeBuilderSetting {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let fork = input.fork();
+        if fork.parse::<syn::Ident>().is_ok_and(|ident| ident == "build_method") && fork.peek(syn::token::Paren) {
+            input.parse::<syn::Ident>()?;
+            let content;
+            syn::parenthesized!(content in input);
+            return Ok(Self::BuildMethod(Punctuated::parse_terminated(&content)?));
+        }
+        input.parse().map(Self::Other)
+    }
+}
+
+/// A setting inside `build_method(...)`.
+enum BuildMethodSetting {
+    Async(syn::Token![async]),
+    Other(syn::Expr),
+}
+
+impl Parse for BuildMethodSetting {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let fork = input.fork();
+        if fork.parse::<syn::Token![async]>().is_ok() && (fork.is_empty() || fork.peek(syn::Token![,])) {
+            return input.parse().map(Self::Async);
+        }
+        input.parse().map(Self::Other)
+    }
+}
@@ -1,5 +1,10 @@
 use quote::ToTokens;
-use syn::{parse::Parser, Error};
+use syn::{
+    parse::Parser,
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Error,
+};
 
 pub fn path_to_single_string(path: &syn::Path) -> Option<String> {
     if path.leading_colon.is_some() {
@@ -85,6 +90,260 @@ pub fn strip_raw_ident_prefix(mut name: String) -> String {
     name
 }
 
+pub fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|j| chars[j]);
+            let next = chars.get(i + 1);
+            let starts_word = match prev {
+                None | Some('_') => false,
+                Some(prev) => prev.is_lowercase() || prev.is_numeric() || next.is_some_and(|next| next.is_lowercase()),
+            };
+            if starts_word {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Turn a message into text that can be used in an identifier, e.g. `Missing one of: a, b` into
+/// `Missing_one_of_a_b`.
+pub fn to_identifier_text(message: &str) -> String {
+    message
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// The identifiers in `expr` that may refer to local variables - i.e. single-segment paths that
+/// aren't bound by a pattern (of a `let`, closure parameter, `if let`, `while let`, `for` or `match`
+/// arm) in scope, and the variables captured inline by format strings.
+pub fn local_idents(expr: &syn::Expr) -> Vec<proc_macro2::Ident> {
+    let mut visitor = LocalIdents::default();
+    visitor.visit_expr(expr);
+    visitor.result
+}
+
+#[derive(Default)]
+struct LocalIdents {
+    /// The variables bound in the scopes entered so far.
+    bound: Vec<proc_macro2::Ident>,
+    result: Vec<proc_macro2::Ident>,
+}
+
+impl LocalIdents {
+    fn reference(&mut self, ident: &proc_macro2::Ident) {
+        if !self.bound.contains(ident) {
+            self.result.push(ident.clone());
+        }
+    }
+
+    fn bind(&mut self, pat: &syn::Pat) {
+        PatBindings(&mut self.bound).visit_pat(pat);
+    }
+
+    /// Run `f` in a new scope, unbinding the variables it binds once it's done.
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        let len = self.bound.len();
+        f(self);
+        self.bound.truncate(len);
+    }
+
+    /// Visit the condition of an `if` or a `while`, binding the variables of its `let`s.
+    fn visit_condition(&mut self, cond: &syn::Expr) {
+        match cond {
+            syn::Expr::Let(expr_let) => {
+                self.visit_expr(&expr_let.expr);
+                self.bind(&expr_let.pat);
+            }
+            syn::Expr::Binary(binary) if matches!(binary.op, syn::BinOp::And(_)) => {
+                self.visit_condition(&binary.left);
+                self.visit_condition(&binary.right);
+            }
+            _ => self.visit_expr(cond),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for LocalIdents {
+    fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+        if expr.qself.is_none() {
+            if let Some(ident) = expr.path.get_ident() {
+                self.reference(ident);
+            }
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.scoped(|this| visit::visit_block(this, block));
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        self.bind(&local.pat);
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        self.scoped(|this| {
+            for input in &closure.inputs {
+                this.bind(input);
+            }
+            this.visit_expr(&closure.body);
+        });
+    }
+
+    fn visit_expr_if(&mut self, expr_if: &'ast syn::ExprIf) {
+        self.scoped(|this| {
+            this.visit_condition(&expr_if.cond);
+            this.visit_block(&expr_if.then_branch);
+        });
+        if let Some((_, else_branch)) = &expr_if.else_branch {
+            self.visit_expr(else_branch);
+        }
+    }
+
+    fn visit_expr_while(&mut self, expr_while: &'ast syn::ExprWhile) {
+        self.scoped(|this| {
+            this.visit_condition(&expr_while.cond);
+            this.visit_block(&expr_while.body);
+        });
+    }
+
+    fn visit_expr_for_loop(&mut self, for_loop: &'ast syn::ExprForLoop) {
+        self.visit_expr(&for_loop.expr);
+        self.scoped(|this| {
+            this.bind(&for_loop.pat);
+            this.visit_block(&for_loop.body);
+        });
+    }
+
+    fn visit_arm(&mut self, arm: &'ast syn::Arm) {
+        self.scoped(|this| {
+            this.bind(&arm.pat);
+            if let Some((_, guard)) = &arm.guard {
+                this.visit_expr(guard);
+            }
+            this.visit_expr(&arm.body);
+        });
+    }
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {
+        // Items can't refer to local variables.
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let Ok(args) = mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated) else {
+            // Not a list of expressions, so assume any of its identifiers may be a variable.
+            for ident in all_idents(mac.tokens.clone()) {
+                self.reference(&ident);
+            }
+            return;
+        };
+        // The names of `name = value` arguments, which format strings refer to instead of variables.
+        let mut named_args = Vec::new();
+        for arg in &args {
+            if let syn::Expr::Assign(assign) = arg {
+                if let syn::Expr::Path(path) = &*assign.left {
+                    if let Some(name) = path.path.get_ident() {
+                        named_args.push(name.clone());
+                        self.visit_expr(&assign.right);
+                        continue;
+                    }
+                }
+            }
+            self.visit_expr(arg);
+        }
+        let format = args.iter().find_map(|arg| match arg {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(format),
+                ..
+            }) => Some(format),
+            _ => None,
+        });
+        if let Some(format) = format {
+            for capture in format_captures(&format.value()) {
+                if let Ok(ident) = syn::parse_str::<proc_macro2::Ident>(capture) {
+                    if !named_args.contains(&ident) {
+                        self.reference(&ident);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Collects the variables bound by a pattern.
+struct PatBindings<'b>(&'b mut Vec<proc_macro2::Ident>);
+
+impl<'ast> Visit<'ast> for PatBindings<'_> {
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        self.0.push(pat.ident.clone());
+        visit::visit_pat_ident(self, pat);
+    }
+
+    fn visit_expr(&mut self, _: &'ast syn::Expr) {
+        // Constants and literals in patterns don't bind anything.
+    }
+}
+
+/// The names a format string captures inline - e.g. `host`, `port` and `width` in
+/// `"{host}:{port:>width$}"`.
+fn format_captures(format: &str) -> Vec<&str> {
+    let is_name = |name: &str| name.starts_with(|c: char| c.is_alphabetic() || c == '_');
+    let mut captures = Vec::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        if let Some(escaped) = rest.strip_prefix('{') {
+            rest = escaped;
+            continue;
+        }
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let (argument, spec) = rest[..end].split_once(':').unwrap_or((&rest[..end], ""));
+        let argument = argument.trim();
+        if is_name(argument) {
+            captures.push(argument);
+        }
+        // Widths and precisions can be captured too, as `name$`.
+        let mut parts = spec.split('$').collect::<Vec<_>>();
+        parts.pop();
+        for part in parts {
+            let name_start = part.rfind(|c: char| !(c.is_alphanumeric() || c == '_')).map_or(0, |i| i + 1);
+            if is_name(&part[name_start..]) {
+                captures.push(&part[name_start..]);
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    captures
+}
+
+/// All the identifiers in `tokens`, including those nested in groups.
+fn all_idents(tokens: proc_macro2::TokenStream) -> Vec<proc_macro2::Ident> {
+    tokens
+        .into_iter()
+        .flat_map(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) => vec![ident],
+            proc_macro2::TokenTree::Group(group) => all_idents(group.stream()),
+            proc_macro2::TokenTree::Punct(_) | proc_macro2::TokenTree::Literal(_) => Vec::new(),
+        })
+        .collect()
+}
+
 pub fn first_visibility(visibilities: &[Option<&syn::Visibility>]) -> proc_macro2::TokenStream {
     let vis = visibilities
         .iter()